@@ -0,0 +1,452 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Block, Error, Expr, FnArg, ImplItem, Item, ItemFn, ItemImpl,
+    LitStr, Pat, ReturnType, Signature, Type,
+};
+
+// declares a node from a function, or from an impl block with an `operation` method
+//
+// the parameters of the function are the input sockets, named after the parameters and described
+// by their doc comments, `#[default(value)]` gives a socket a default value
+// the return value is the output, a tuple gives the node more than one output socket
+// returning a `Result<_, NodeError>` makes the node fallible, the graph reports the error
+//
+// `#[node(name = "...", identifier = "...", description = "...", output("Name", "description"))]`
+// everything is optional on a function, the name and identifier come from the function name and
+// the description from the doc comment, an impl block needs a name and an identifier
+//
+// on a function this generates a `{Name}Node` struct with a `register` function, on an impl block
+// the `Node` impl is generated for the type, taking its `parameters`, `set_parameter` and
+// `is_output` methods
+#[proc_macro_attribute]
+pub fn node(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = NodeArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with parser);
+
+    let result = match parse_macro_input!(item as Item) {
+        Item::Fn(function) => node_from_fn(args, function),
+        Item::Impl(block) => node_from_impl(args, block),
+        item => Err(Error::new_spanned(
+            item,
+            "#[node] goes on a function or an impl block",
+        )),
+    };
+
+    result.unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct NodeArgs {
+    name: Option<Expr>,
+    identifier: Option<Expr>,
+    description: Option<Expr>,
+    outputs: Vec<(LitStr, LitStr)>,
+}
+
+impl NodeArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("identifier") {
+            self.identifier = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("output") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let name: LitStr = content.parse()?;
+            let description = if content.is_empty() {
+                LitStr::new("", Span::call_site())
+            } else {
+                content.parse::<syn::Token![,]>()?;
+                content.parse()?
+            };
+            self.outputs.push((name, description));
+        } else {
+            return Err(meta.error("expected name, identifier, description or output"));
+        }
+        Ok(())
+    }
+}
+
+// an input socket, made from a parameter of the operation
+struct Input {
+    pat: Pat,
+    ident: syn::Ident,
+    ty: Type,
+    description: String,
+    default: Option<Expr>,
+}
+
+// everything the `Node` impl needs from the signature of the operation
+struct Operation {
+    inputs: Vec<Input>,
+    outputs: Vec<Type>,
+    // whether the function returns a tuple, rather than a single output
+    returns_tuple: bool,
+    // whether the function returns a `Result` with its outputs, making the node fallible
+    fallible: bool,
+}
+
+impl Operation {
+    // reads the signature, removing the attributes only the macro understands
+    fn from_signature(signature: &mut Signature) -> syn::Result<Self> {
+        let mut inputs = Vec::new();
+        for argument in signature.inputs.iter_mut() {
+            let FnArg::Typed(argument) = argument else {
+                continue;
+            };
+            let Pat::Ident(pat_ident) = argument.pat.as_ref() else {
+                return Err(Error::new_spanned(
+                    &argument.pat,
+                    "the parameters of a node need names, they are used for the sockets",
+                ));
+            };
+
+            let mut default = None;
+            let mut description = Vec::new();
+            let mut error = None;
+            argument.attrs.retain(|attribute| {
+                if attribute.path().is_ident("default") {
+                    match attribute.parse_args() {
+                        Ok(expr) => default = Some(expr),
+                        Err(parse_error) => error = Some(parse_error),
+                    }
+                    false
+                } else if let Some(line) = doc_line(attribute) {
+                    description.push(line);
+                    false
+                } else {
+                    true
+                }
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+
+            inputs.push(Input {
+                pat: argument.pat.as_ref().clone(),
+                ident: pat_ident.ident.clone(),
+                ty: argument.ty.as_ref().clone(),
+                description: description.join(" "),
+                default,
+            });
+        }
+
+        let returned = match &signature.output {
+            ReturnType::Default => {
+                return Err(Error::new_spanned(
+                    &signature.ident,
+                    "a node needs to return its outputs",
+                ))
+            }
+            ReturnType::Type(_, ty) => ty.as_ref(),
+        };
+        let (returned, fallible) = match result_ok_type(returned) {
+            Some(ok) => (ok, true),
+            None => (returned, false),
+        };
+        let (outputs, returns_tuple) = match returned {
+            Type::Tuple(tuple) => (tuple.elems.iter().cloned().collect(), true),
+            ty => (vec![ty.clone()], false),
+        };
+
+        Ok(Self {
+            inputs,
+            outputs,
+            returns_tuple,
+            fallible,
+        })
+    }
+
+    fn input_type(&self) -> TokenStream2 {
+        let types = self.inputs.iter().map(|input| &input.ty);
+        quote!((#(#types,)*))
+    }
+
+    // the parameters of a function are passed on by name, an impl uses them as they were written
+    fn input_pattern(&self, by_name: bool) -> TokenStream2 {
+        if by_name {
+            let names = self.inputs.iter().map(|input| &input.ident);
+            return quote!((#(#names,)*));
+        }
+        let patterns = self.inputs.iter().map(|input| &input.pat);
+        quote!((#(#patterns,)*))
+    }
+
+    fn output_type(&self) -> TokenStream2 {
+        let types = self.outputs.iter();
+        quote!((#(#types,)*))
+    }
+
+    // the method of the `Node` impl, turning what the operation returned into the output tuple
+    fn method(&self, pattern: TokenStream2, result: TokenStream2) -> TokenStream2 {
+        let input_type = self.input_type();
+        let output_type = self.output_type();
+        // an operation that can't fail always succeeds
+        let wrapped = match (self.fallible, self.returns_tuple) {
+            (true, true) => quote!(#result),
+            (true, false) => quote!(#result.map(|output| (output,))),
+            (false, true) => quote!(::std::result::Result::Ok(#result)),
+            (false, false) => quote!(::std::result::Result::Ok((#result,))),
+        };
+        quote! {
+            fn try_operation(
+                &self,
+                #pattern: #input_type,
+            ) -> ::std::result::Result<#output_type, ::mesh_mancer::NodeError> {
+                #wrapped
+            }
+        }
+    }
+
+    fn socket_functions(&self, args: &NodeArgs) -> TokenStream2 {
+        let inputs = self.inputs.iter().map(|input| {
+            let name = socket_name(&input.ident.to_string());
+            let description = &input.description;
+            let default = input
+                .default
+                .as_ref()
+                .map(|default| quote!(.with_default(#default)));
+            quote!(::mesh_mancer::socket::SocketDescriptor::new(#name, #description) #default)
+        });
+        let outputs = args
+            .outputs
+            .iter()
+            .map(|(name, description)| {
+                quote!(::mesh_mancer::socket::SocketDescriptor::new(#name, #description))
+            });
+
+        quote! {
+            fn inputs() -> Vec<::mesh_mancer::socket::SocketDescriptor> {
+                vec![#(#inputs),*]
+            }
+
+            fn outputs() -> Vec<::mesh_mancer::socket::SocketDescriptor> {
+                vec![#(#outputs),*]
+            }
+        }
+    }
+}
+
+fn node_from_fn(args: NodeArgs, mut function: ItemFn) -> syn::Result<TokenStream2> {
+    let operation = Operation::from_signature(&mut function.sig)?;
+
+    let function_name = function.sig.ident.to_string();
+    let struct_name = format_ident!("{}Node", camel_case(&function_name));
+    let function_ident = &function.sig.ident;
+    let visibility = &function.vis;
+
+    let name = args
+        .name
+        .clone()
+        .map(|name| quote!(#name))
+        .unwrap_or_else(|| {
+            let name = socket_name(&function_name);
+            quote!(#name)
+        });
+    let identifier = args
+        .identifier
+        .clone()
+        .map(|identifier| quote!(#identifier))
+        .unwrap_or_else(|| quote!(#function_name));
+    let description = description(&args, &function.attrs);
+
+    let input_type = operation.input_type();
+    let output_type = operation.output_type();
+    let arguments = operation.inputs.iter().map(|input| &input.ident);
+    let method = operation.method(
+        operation.input_pattern(true),
+        quote!(#function_ident(#(#arguments),*)),
+    );
+    let sockets = operation.socket_functions(&args);
+
+    Ok(quote! {
+        #function
+
+        #[derive(Clone)]
+        #visibility struct #struct_name {}
+
+        impl ::mesh_mancer::Node<#input_type, #output_type> for #struct_name {
+            #method
+
+            fn name() -> String {
+                (#name).to_string()
+            }
+
+            fn description() -> String {
+                (#description).to_string()
+            }
+
+            fn identifier() -> String {
+                (#identifier).to_string()
+            }
+
+            #sockets
+        }
+
+        impl #struct_name {
+            // registers the node so that it can be created by its identifier
+            pub fn register(registry: &mut ::mesh_mancer::registry::NodeRegistry) {
+                registry.register(|| #struct_name {});
+            }
+        }
+    })
+}
+
+fn node_from_impl(args: NodeArgs, block: ItemImpl) -> syn::Result<TokenStream2> {
+    let mut operation = None;
+    let mut methods = Vec::new();
+    for item in block.items.iter() {
+        match item {
+            ImplItem::Fn(method) if method.sig.ident == "operation" => {
+                operation = Some(method.clone())
+            }
+            ImplItem::Fn(method)
+                if ["parameters", "set_parameter", "is_output"]
+                    .iter()
+                    .any(|name| method.sig.ident == name) =>
+            {
+                methods.push(method.clone())
+            }
+            item => return Err(Error::new_spanned(
+                item,
+                "only operation, parameters, set_parameter and is_output can be in a #[node] impl",
+            )),
+        }
+    }
+    let Some(mut method) = operation else {
+        return Err(Error::new_spanned(
+            &block.self_ty,
+            "a #[node] impl needs an operation method",
+        ));
+    };
+    let node_operation = Operation::from_signature(&mut method.sig)?;
+
+    let self_ty = &block.self_ty;
+    let (impl_generics, _, where_clause) = block.generics.split_for_impl();
+
+    let name = args
+        .name
+        .clone()
+        .map(|name| quote!(#name))
+        .ok_or_else(|| Error::new_spanned(self_ty, "a #[node] impl needs a name = \"...\""))?;
+    // saved graphs refer to the node by its identifier, so it is given instead of taken from the type
+    let identifier = args
+        .identifier
+        .clone()
+        .map(|identifier| quote!(#identifier))
+        .ok_or_else(|| {
+            Error::new_spanned(self_ty, "a #[node] impl needs an identifier = \"...\"")
+        })?;
+    let description = description(&args, &block.attrs);
+
+    let input_type = node_operation.input_type();
+    let output_type = node_operation.output_type();
+    let body: &Block = &method.block;
+    let return_type = match &method.sig.output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => unreachable!("checked by Operation::from_signature"),
+    };
+    let node_method = node_operation.method(
+        node_operation.input_pattern(false),
+        quote!({
+            let result: #return_type = (|| #body)();
+            result
+        }),
+    );
+    let sockets = node_operation.socket_functions(&args);
+
+    Ok(quote! {
+        impl #impl_generics ::mesh_mancer::Node<#input_type, #output_type> for #self_ty #where_clause {
+            #node_method
+
+            fn name() -> String {
+                (#name).to_string()
+            }
+
+            fn description() -> String {
+                (#description).to_string()
+            }
+
+            fn identifier() -> String {
+                (#identifier).to_string()
+            }
+
+            #sockets
+
+            #(#methods)*
+        }
+    })
+}
+
+// the `T` of a `Result<T, E>`, the error type is left for the compiler to check
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        syn::GenericArgument::Type(ok) => Some(ok),
+        _ => None,
+    }
+}
+
+// the description given to the macro, or the doc comment of the item
+fn description(args: &NodeArgs, attributes: &[Attribute]) -> TokenStream2 {
+    if let Some(description) = &args.description {
+        return quote!(#description);
+    }
+    let lines: Vec<String> = attributes.iter().filter_map(doc_line).collect();
+    let description = lines.join(" ");
+    quote!(#description)
+}
+
+fn doc_line(attribute: &Attribute) -> Option<String> {
+    if !attribute.path().is_ident("doc") {
+        return None;
+    }
+    let syn::Meta::NameValue(name_value) = &attribute.meta else {
+        return None;
+    };
+    let Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(line),
+        ..
+    }) = &name_value.value
+    else {
+        return None;
+    };
+    Some(line.value().trim().to_string())
+}
+
+// `scale_instance` becomes `Scale instance`
+fn socket_name(name: &str) -> String {
+    let name = name.trim_start_matches('_').replace('_', " ");
+    let mut characters = name.chars();
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => name,
+    }
+}
+
+// `scale_instance` becomes `ScaleInstance`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut characters = word.chars();
+            match characters.next() {
+                Some(first) => first.to_uppercase().chain(characters).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,136 @@
+// evaluates a saved graph without opening the editor and writes its output model to an obj file
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use mesh_mancer::graph_file::GraphDescription;
+use mesh_mancer::overrides::Override;
+use mesh_mancer::NodeGraph;
+
+const USAGE: &str = "usage: mesh_mancer_eval <graph.ron> <mesh.obj> [--output <name>] [--set [node.]<name>=<value>]...";
+
+#[derive(Debug, PartialEq)]
+struct Arguments {
+    graph: PathBuf,
+    mesh: PathBuf,
+    // the output node to write, the first one when None
+    output: Option<String>,
+    overrides: Vec<Override>,
+}
+
+impl Arguments {
+    fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        let mut output = None;
+        let mut overrides = Vec::new();
+
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--output" => {
+                    output = Some(
+                        arguments
+                            .next()
+                            .ok_or("--output needs the name of an output")?,
+                    );
+                }
+                "--set" => {
+                    let text = arguments.next().ok_or("--set needs a name=value")?;
+                    overrides.push(text.parse().map_err(|error| format!("{}", error))?);
+                }
+                _ if argument.starts_with("--") => {
+                    return Err(format!("unknown option {}", argument));
+                }
+                _ => paths.push(PathBuf::from(argument)),
+            }
+        }
+
+        let [graph, mesh] = <[PathBuf; 2]>::try_from(paths)
+            .map_err(|_| "expected the graph file and the mesh file to write".to_string())?;
+        Ok(Self {
+            graph,
+            mesh,
+            output,
+            overrides,
+        })
+    }
+}
+
+fn run(arguments: Arguments) -> Result<(), String> {
+    let description = GraphDescription::load(&arguments.graph)
+        .map_err(|error| format!("couldn't load {}: {}", arguments.graph.display(), error))?;
+    let mut node_graph = NodeGraph::from_description(&description)
+        .map_err(|error| format!("couldn't load {}: {}", arguments.graph.display(), error))?;
+
+    for value in arguments.overrides.iter() {
+        value
+            .apply(&mut node_graph)
+            .map_err(|error| format!("couldn't set {}: {}", value.name, error))?;
+    }
+
+    let model = match &arguments.output {
+        Some(name) => node_graph.get_named_output(name),
+        None => node_graph.get_output(),
+    };
+    for diagnostic in node_graph.get_diagnostics() {
+        eprintln!("{}", diagnostic);
+    }
+    let model = model.map_err(|error| node_graph.describe_error(&error))?;
+
+    model
+        .save_obj(&arguments.mesh)
+        .map_err(|error| format!("couldn't write {}: {}", arguments.mesh.display(), error))?;
+    eprintln!(
+        "wrote {} vertices and {} triangles to {}",
+        model.vertices().len(),
+        model.triangle_count(),
+        arguments.mesh.display()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let arguments = match Arguments::parse(std::env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(arguments) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Arguments, String> {
+        Arguments::parse(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let arguments = parse(&[
+            "graph.ron",
+            "--set",
+            "scale=0.2",
+            "mesh.obj",
+            "--output",
+            "render",
+        ])
+        .unwrap();
+        assert_eq!(arguments.graph, PathBuf::from("graph.ron"));
+        assert_eq!(arguments.mesh, PathBuf::from("mesh.obj"));
+        assert_eq!(arguments.output.as_deref(), Some("render"));
+        assert_eq!(arguments.overrides, vec!["scale=0.2".parse().unwrap()]);
+
+        assert!(parse(&["graph.ron"]).is_err());
+        assert!(parse(&["graph.ron", "mesh.obj", "--set"]).is_err());
+        assert!(parse(&["graph.ron", "mesh.obj", "--verbose"]).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use three_d::Vector3;
+
+use crate::geometry::{Curve, PointCloud};
+use crate::Model;
+
+type ConvertFn = Arc<dyn Fn(&dyn Any) -> Option<Box<dyn Any + Send + Sync>> + Send + Sync>;
+
+// the conversions the graph applies when an output is connected to an input of another type
+#[derive(Clone)]
+pub struct Conversions {
+    conversions: HashMap<(TypeId, TypeId), ConvertFn>,
+}
+
+impl Conversions {
+    // a table without any conversions, use `default` to get the built-in ones
+    pub fn new() -> Self {
+        Self {
+            conversions: HashMap::new(),
+        }
+    }
+
+    // registers a conversion from `A` to `B`, replacing any conversion registered before
+    pub fn register<A, B>(&mut self, convert: impl Fn(&A) -> B + Send + Sync + 'static)
+    where
+        A: 'static,
+        B: Send + Sync + 'static,
+    {
+        self.conversions.insert(
+            (TypeId::of::<A>(), TypeId::of::<B>()),
+            Arc::new(move |value| {
+                value
+                    .downcast_ref::<A>()
+                    .map(|value| Box::new(convert(value)) as Box<dyn Any + Send + Sync>)
+            }),
+        );
+    }
+
+    // whether a value of type `from` can be used where a `to` is needed
+    pub fn can_convert(&self, from: TypeId, to: TypeId) -> bool {
+        from == to || self.conversions.contains_key(&(from, to))
+    }
+
+    // converts a value of type `from` to `to`, None if there is no conversion or the value has another type
+    pub fn convert(
+        &self,
+        value: &dyn Any,
+        from: TypeId,
+        to: TypeId,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        self.conversions
+            .get(&(from, to))
+            .and_then(|convert| convert(value))
+    }
+}
+
+impl Default for Conversions {
+    fn default() -> Self {
+        let mut conversions = Self::new();
+
+        // a scalar is used for every component of a vector
+        conversions.register(|value: &f32| Vector3::new(*value, *value, *value));
+        conversions.register(|value: &i32| *value as f32);
+        conversions.register(|value: &i32| {
+            let value = *value as f32;
+            Vector3::new(value, value, value)
+        });
+
+        // a single model is a list with one model, and a list of models is merged into one
+        conversions.register(|model: &Model| vec![model.clone()]);
+        conversions.register(|models: &Vec<Model>| {
+            let mut merged = Model::new();
+            for model in models {
+                merged.merge(model);
+            }
+            merged
+        });
+
+        // the points of a model or a curve
+        conversions.register(|model: &Model| PointCloud::from(model));
+        conversions.register(|curve: &Curve| PointCloud::from(curve));
+
+        conversions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_conversions() {
+        let conversions = Conversions::default();
+
+        let vector = conversions
+            .convert(&2.0f32, TypeId::of::<f32>(), TypeId::of::<Vector3<f32>>())
+            .unwrap();
+        assert_eq!(
+            vector.downcast_ref::<Vector3<f32>>(),
+            Some(&Vector3::new(2.0, 2.0, 2.0))
+        );
+
+        let float = conversions
+            .convert(&3i32, TypeId::of::<i32>(), TypeId::of::<f32>())
+            .unwrap();
+        assert_eq!(float.downcast_ref::<f32>(), Some(&3.0));
+
+        assert!(conversions.can_convert(TypeId::of::<Model>(), TypeId::of::<Vec<Model>>()));
+        assert!(!conversions.can_convert(TypeId::of::<Vector3<f32>>(), TypeId::of::<f32>()));
+    }
+}
@@ -0,0 +1,82 @@
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::NodeId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warning,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "info"),
+            Level::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// a message a node emitted while it ran
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub node: NodeId,
+    pub level: Level,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} from node {}: {}",
+            self.level, self.node, self.message
+        )
+    }
+}
+
+thread_local! {
+    // the messages of the node running on this thread, None when no node is running
+    static MESSAGES: RefCell<Option<Vec<(Level, String)>>> = const { RefCell::new(None) };
+}
+
+// emits a message from the operation of a node, it is ignored outside of an evaluation
+// the message has to be emitted on the thread running the operation, messages emitted from
+// other threads, like from a rayon iterator inside of the node, are dropped
+pub fn info(message: impl Into<String>) {
+    emit(Level::Info, message.into());
+}
+
+// like `info`, only on the thread running the operation
+pub fn warning(message: impl Into<String>) {
+    emit(Level::Warning, message.into());
+}
+
+pub(crate) fn emit(level: Level, message: String) {
+    MESSAGES.with(|messages| {
+        if let Some(messages) = messages.borrow_mut().as_mut() {
+            messages.push((level, message));
+        }
+    });
+}
+
+// runs a node, collecting the messages it emits
+// a node runs on a single thread, so the messages of nodes running at the same time don't mix
+// the messages collected around this call, like those of the group running a node, are kept
+pub(crate) fn collect<R>(node: NodeId, run: impl FnOnce() -> R) -> (R, Vec<Diagnostic>) {
+    let outer = MESSAGES.with(|messages| messages.replace(Some(Vec::new())));
+    let result = run();
+    let messages = MESSAGES.with(|messages| messages.replace(outer));
+
+    let diagnostics = messages
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(level, message)| Diagnostic {
+            node,
+            level,
+            message,
+        })
+        .collect();
+    (result, diagnostics)
+}
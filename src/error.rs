@@ -0,0 +1,279 @@
+use std::any::TypeId;
+use std::fmt;
+
+use crate::{NodeId, NodeSocket};
+
+// an error produced while turning the raw inputs of a node into its typed input tuple
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    MissingInput { socket: usize },
+    DowncastFailed { socket: usize },
+    // the node couldn't make its outputs, like a group whose inner graph has an error
+    Failed { message: String },
+}
+
+// the error a node returns when it can't make its outputs from the inputs it got
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeError {
+    message: String,
+}
+
+impl NodeError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for NodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for NodeError {}
+
+impl From<NodeError> for ConvertError {
+    fn from(error: NodeError) -> Self {
+        ConvertError::Failed {
+            message: error.message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    // the output type of `from` doesn't match the input type of `to`
+    TypeMismatch {
+        from: NodeSocket,
+        to: NodeSocket,
+        expected: TypeId,
+        found: TypeId,
+    },
+    // there is no node with this id in the graph
+    NodeNotFound {
+        node: NodeId,
+    },
+    // a connection refers to a socket the node doesn't have
+    SocketOutOfRange {
+        node: NodeId,
+        socket: usize,
+    },
+    // the connection from `from` to `to` makes a node depend on its own output
+    Cycle {
+        from: NodeSocket,
+        to: NodeSocket,
+    },
+    // nothing is connected to an input socket that needs a value
+    MissingInput {
+        node: NodeId,
+        socket: usize,
+    },
+    // the default value doesn't have the type of the socket
+    InvalidDefault {
+        node: NodeId,
+        socket: usize,
+    },
+    // the graph has no output node to evaluate
+    NoOutputNode,
+    // no output node has this name
+    OutputNotFound {
+        name: String,
+    },
+    // more than one output node has this name
+    DuplicateOutput {
+        name: String,
+    },
+    // the output was asked for as another type than the one it has
+    OutputTypeMismatch {
+        node: NodeId,
+        expected: String,
+    },
+    // a value couldn't be downcast to the type the socket expects
+    DowncastFailed {
+        node: NodeId,
+        socket: usize,
+    },
+    // the operation of a node failed
+    NodeFailed {
+        node: NodeId,
+        message: String,
+    },
+    // another group used in the graph already has this name, they would be saved as one
+    DuplicateGroup {
+        name: String,
+    },
+    // an instance of the group would be inside of the group itself
+    RecursiveGroup {
+        name: String,
+    },
+    // a group needs at least one node
+    EmptyGroup,
+    // output nodes stay in the graph they are the output of, they can't be grouped
+    GroupedOutput {
+        node: NodeId,
+    },
+}
+
+impl GraphError {
+    pub(crate) fn from_convert(node: NodeId, error: ConvertError) -> Self {
+        match error {
+            ConvertError::MissingInput { socket } => GraphError::MissingInput { node, socket },
+            ConvertError::DowncastFailed { socket } => GraphError::DowncastFailed { node, socket },
+            ConvertError::Failed { message } => GraphError::NodeFailed { node, message },
+        }
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::TypeMismatch {
+                from,
+                to,
+                expected,
+                found,
+            } => write!(
+                f,
+                "invalid type for input, expected {:?}, got {:?}. from node {} socket {}, to node {} socket {}",
+                expected,
+                found,
+                from.node(),
+                from.socket(),
+                to.node(),
+                to.socket()
+            ),
+            GraphError::NodeNotFound { node } => write!(f, "there is no node {}", node),
+            GraphError::SocketOutOfRange { node, socket } => {
+                write!(f, "node {} has no socket {}", node, socket)
+            }
+            GraphError::Cycle { from, to } => write!(
+                f,
+                "connecting node {} socket {} to node {} socket {} creates a cycle",
+                from.node(),
+                from.socket(),
+                to.node(),
+                to.socket()
+            ),
+            GraphError::MissingInput { node, socket } => {
+                write!(f, "nothing is connected to socket {} of node {}", socket, node)
+            }
+            GraphError::InvalidDefault { node, socket } => write!(
+                f,
+                "the default value of socket {} of node {} has the wrong type",
+                socket, node
+            ),
+            GraphError::NoOutputNode => write!(f, "the graph has no output node"),
+            GraphError::OutputNotFound { name } => write!(f, "the graph has no output {}", name),
+            GraphError::DuplicateOutput { name } => {
+                write!(f, "more than one output is named {}", name)
+            }
+            GraphError::OutputTypeMismatch { node, expected } => {
+                write!(f, "the output of node {} is not a {}", node, expected)
+            }
+            GraphError::DowncastFailed { node, socket } => {
+                write!(f, "wrong type for socket {} of node {}", socket, node)
+            }
+            GraphError::NodeFailed { node, message } => {
+                write!(f, "node {} failed: {}", node, message)
+            }
+            GraphError::DuplicateGroup { name } => {
+                write!(f, "there already is a group named {}", name)
+            }
+            GraphError::RecursiveGroup { name } => {
+                write!(f, "the group {} can't contain itself", name)
+            }
+            GraphError::EmptyGroup => write!(f, "a group needs at least one node"),
+            GraphError::GroupedOutput { node } => {
+                write!(f, "the output node {} can't be put in a group", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+// an error produced while saving or loading a graph
+#[derive(Debug)]
+pub enum FileError {
+    Io(std::io::Error),
+    // the file isn't a valid graph description
+    Format(String),
+    // no node is known by this identifier
+    UnknownNodeType(String),
+    // the node has no parameter with this name, or it has a different type
+    InvalidParameter { node: NodeId, name: String },
+    // more than one node of the file has this id
+    DuplicateNode(NodeId),
+    // the connections of the graph are invalid
+    Graph(GraphError),
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(error: std::io::Error) -> Self {
+        FileError::Io(error)
+    }
+}
+
+impl From<GraphError> for FileError {
+    fn from(error: GraphError) -> Self {
+        FileError::Graph(error)
+    }
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(error) => write!(f, "{}", error),
+            FileError::Format(message) => write!(f, "invalid graph file: {}", message),
+            FileError::UnknownNodeType(identifier) => {
+                write!(f, "unknown node type {}", identifier)
+            }
+            FileError::InvalidParameter { node, name } => {
+                write!(f, "node {} has no parameter {}", node, name)
+            }
+            FileError::DuplicateNode(node) => write!(f, "more than one node has the id {}", node),
+            FileError::Graph(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+// an error produced while setting a value of a graph from outside, like `--set scale=0.2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideError {
+    // the override isn't written as `name=value` or `node.name=value`
+    Syntax(String),
+    // no parameter or unconnected input socket has this name
+    NotFound { name: String },
+    // the value can't be read as the type of the parameter or socket
+    InvalidValue { name: String, value: String },
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::Syntax(text) => {
+                write!(
+                    f,
+                    "{} is not of the form name=value or node.name=value",
+                    text
+                )
+            }
+            OverrideError::NotFound { name } => {
+                write!(f, "no parameter or unconnected input is named {}", name)
+            }
+            OverrideError::InvalidValue { name, value } => {
+                write!(f, "{} is not a valid value for {}", value, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OverrideError {}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use three_d::{InnerSpace, Point3, Transform, Vector3};
+
+use crate::Model;
+
+// a set of points without any faces, like the vertices of a model
+// the points are shared between clones like the buffers of a model
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointCloud {
+    pub(crate) points: Arc<Vec<Vector3<f32>>>,
+}
+
+impl PointCloud {
+    pub fn new(points: Vec<Vector3<f32>>) -> Self {
+        Self {
+            points: Arc::new(points),
+        }
+    }
+
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    // copies the points first if another clone still uses them
+    pub fn points_mut(&mut self) -> &mut Vec<Vector3<f32>> {
+        Arc::make_mut(&mut self.points)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+// the vertices of the model, moved by its transform
+impl From<&Model> for PointCloud {
+    fn from(model: &Model) -> Self {
+        let points = model
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let point = model
+                    .transform
+                    .transform_point(Point3::new(vertex.x, vertex.y, vertex.z));
+                Vector3::new(point.x, point.y, point.z)
+            })
+            .collect();
+        Self::new(points)
+    }
+}
+
+// a line through a list of points, going back to the first point when closed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Curve {
+    pub(crate) points: Arc<Vec<Vector3<f32>>>,
+    pub closed: bool,
+}
+
+impl Curve {
+    pub fn new(points: Vec<Vector3<f32>>, closed: bool) -> Self {
+        Self {
+            points: Arc::new(points),
+            closed,
+        }
+    }
+
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    // copies the points first if another clone still uses them
+    pub fn points_mut(&mut self) -> &mut Vec<Vector3<f32>> {
+        Arc::make_mut(&mut self.points)
+    }
+
+    // the length of the line, including the segment back to the start of a closed curve
+    pub fn length(&self) -> f32 {
+        let segments = self.points.windows(2).map(|pair| (pair[0], pair[1]));
+        let closing = match (self.closed, self.points.first(), self.points.last()) {
+            (true, Some(first), Some(last)) => Some((*last, *first)),
+            _ => None,
+        };
+        segments
+            .chain(closing)
+            .map(|(a, b)| (b - a).magnitude())
+            .sum()
+    }
+}
+
+// shares the points of the curve instead of copying them
+impl From<&Curve> for PointCloud {
+    fn from(curve: &Curve) -> Self {
+        Self {
+            points: curve.points.clone(),
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{FileError, GraphError};
+use crate::group::{GroupDefinition, GroupSocket, SharedGroup};
+use crate::parameter::ParameterValue;
+use crate::registry::NodeRegistry;
+use crate::{NodeGraph, NodeId, NodeSocket};
+
+// a description of a node graph that can be written to and read from a file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GraphDescription {
+    pub nodes: Vec<NodeDescription>,
+    pub connections: Vec<ConnectionDescription>,
+    // the groups used by the nodes, so the file can be loaded on its own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupDescription>,
+    // the id the next node added gets, so the ids of removed nodes aren't given out again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_id: Option<NodeId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeDescription {
+    pub id: NodeId,
+    // the identifier of the type of the node
+    #[serde(rename = "type")]
+    pub identifier: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, ParameterValue>,
+    // the values set for unconnected input sockets, by socket index
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<usize, ParameterValue>,
+    // where the node is shown in the editor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<[f32; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionDescription {
+    pub from: NodeSocket,
+    pub to: NodeSocket,
+}
+
+// a group definition, saved with the graphs using it or on its own to be reused
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupDescription {
+    pub name: String,
+    pub graph: GraphDescription,
+    pub inputs: Vec<GroupSocket>,
+    pub outputs: Vec<GroupSocket>,
+}
+
+fn from_ron<T: DeserializeOwned>(text: &str) -> Result<T, FileError> {
+    ron::from_str(text).map_err(|error| FileError::Format(error.to_string()))
+}
+
+fn to_ron<T: Serialize>(value: &T) -> Result<String, FileError> {
+    ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| FileError::Format(error.to_string()))
+}
+
+impl GraphDescription {
+    pub fn from_ron(text: &str) -> Result<Self, FileError> {
+        from_ron(text)
+    }
+
+    pub fn to_ron(&self) -> Result<String, FileError> {
+        to_ron(self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FileError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FileError> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn position(&self, id: NodeId) -> Option<[f32; 2]> {
+        self.nodes
+            .iter()
+            .find(|node| node.id == id)
+            .and_then(|node| node.position)
+    }
+}
+
+impl GroupDescription {
+    pub fn from_ron(text: &str) -> Result<Self, FileError> {
+        from_ron(text)
+    }
+
+    pub fn to_ron(&self) -> Result<String, FileError> {
+        to_ron(self)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FileError> {
+        Self::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FileError> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+}
+
+impl GroupDefinition {
+    pub fn to_description(&self) -> GroupDescription {
+        GroupDescription {
+            name: self.name().to_string(),
+            graph: self.graph().to_description(),
+            inputs: self.inputs().to_vec(),
+            outputs: self.outputs().to_vec(),
+        }
+    }
+
+    // builds a group from a description, registering the groups nested in it
+    pub fn from_description(
+        description: &GroupDescription,
+        registry: &mut NodeRegistry,
+    ) -> Result<SharedGroup, FileError> {
+        let graph = NodeGraph::from_description_with_registry(&description.graph, registry)?;
+
+        // the exposed sockets have to exist
+        let inputs = description.inputs.iter().map(|input| (input, true));
+        let outputs = description.outputs.iter().map(|output| (output, false));
+        for (exposed, is_input) in inputs.chain(outputs) {
+            let node = exposed.socket.node();
+            let socket = exposed.socket.socket();
+            let node_any = graph
+                .get_node(node)
+                .ok_or(GraphError::NodeNotFound { node })?;
+            let count = if is_input {
+                node_any.needed_types_input().len()
+            } else {
+                node_any.needed_types_output().len()
+            };
+            if socket >= count {
+                return Err(GraphError::SocketOutOfRange { node, socket }.into());
+            }
+        }
+
+        Ok(SharedGroup::new(GroupDefinition::new(
+            &description.name,
+            graph,
+            description.inputs.clone(),
+            description.outputs.clone(),
+        )))
+    }
+}
+
+impl NodeGraph {
+    // describes the graph without any editor positions
+    pub fn to_description(&self) -> GraphDescription {
+        let nodes = self
+            .get_node_ids()
+            .into_iter()
+            .filter_map(|id| {
+                let node = self.get_node(id)?;
+                Some(NodeDescription {
+                    id,
+                    identifier: node.identifier().to_string(),
+                    parameters: node.parameters().into_iter().collect(),
+                    defaults: self.get_input_defaults(id),
+                    position: None,
+                })
+            })
+            .collect();
+
+        let connections = self
+            .get_connections()
+            .into_iter()
+            .map(|connection| ConnectionDescription {
+                from: connection.from(),
+                to: connection.to(),
+            })
+            .collect();
+
+        // every group used by a node is saved once, the groups nested in it are saved inside of it
+        let mut groups: Vec<GroupDescription> = Vec::new();
+        for node in self.get_nodes() {
+            let Some(group) = node.as_group() else {
+                continue;
+            };
+            let definition = group.definition().read();
+            if groups.iter().all(|group| group.name != definition.name()) {
+                groups.push(definition.to_description());
+            }
+        }
+
+        GraphDescription {
+            nodes,
+            connections,
+            groups,
+            next_id: Some(self.next_id()),
+        }
+    }
+
+    // builds a graph from a description using the built-in nodes
+    pub fn from_description(description: &GraphDescription) -> Result<Self, FileError> {
+        Self::from_description_with_registry(description, &mut NodeRegistry::default())
+    }
+
+    // builds a graph from a description, the nodes keep the ids they were saved with
+    // the groups saved with the graph are added to the registry
+    pub fn from_description_with_registry(
+        description: &GraphDescription,
+        registry: &mut NodeRegistry,
+    ) -> Result<Self, FileError> {
+        for group in description.groups.iter() {
+            let definition = GroupDefinition::from_description(group, registry)?;
+            registry.register_group(definition);
+        }
+
+        let mut node_graph = NodeGraph::new();
+
+        for node_description in description.nodes.iter() {
+            if node_graph.get_node(node_description.id).is_some() {
+                return Err(FileError::DuplicateNode(node_description.id));
+            }
+            let mut node = registry
+                .create(&node_description.identifier)
+                .ok_or_else(|| FileError::UnknownNodeType(node_description.identifier.clone()))?;
+
+            for (name, value) in node_description.parameters.iter() {
+                if !node.set_parameter(name, value.clone()) {
+                    return Err(FileError::InvalidParameter {
+                        node: node_description.id,
+                        name: name.clone(),
+                    });
+                }
+            }
+
+            node_graph.insert_node(node_description.id, node);
+
+            for (socket, value) in node_description.defaults.iter() {
+                node_graph.set_input_default(
+                    &NodeSocket::new(node_description.id, *socket),
+                    value.clone(),
+                )?;
+            }
+        }
+
+        for connection in description.connections.iter() {
+            node_graph.connect(connection.from.clone(), connection.to.clone())?;
+        }
+        if let Some(next_id) = description.next_id {
+            node_graph.reserve_ids(next_id);
+        }
+
+        Ok(node_graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::SphereNode;
+
+    #[test]
+    fn test_round_trip() {
+        let mut node_graph = crate::example().unwrap();
+        let mut description = node_graph.to_description();
+        description.nodes[0].position = Some([10.0, 20.0]);
+
+        let text = description.to_ron().unwrap();
+        let loaded = GraphDescription::from_ron(&text).unwrap();
+        assert_eq!(loaded, description);
+
+        let mut loaded_graph = NodeGraph::from_description(&loaded).unwrap();
+        assert_eq!(loaded_graph.get_node_ids(), node_graph.get_node_ids());
+        assert_eq!(loaded_graph.to_description(), node_graph.to_description());
+        assert_eq!(
+            loaded_graph.get_output().unwrap().vertices,
+            node_graph.get_output().unwrap().vertices
+        );
+    }
+
+    #[test]
+    fn test_ids_after_removing_nodes() {
+        let mut node_graph = crate::example().unwrap();
+        let ids = node_graph.get_node_ids();
+        let last = *ids.last().unwrap();
+        node_graph.remove_node(ids[1]).unwrap();
+        node_graph.remove_node(last).unwrap();
+        let remaining = node_graph.get_node_ids();
+
+        let text = node_graph.to_description().to_ron().unwrap();
+        let description = GraphDescription::from_ron(&text).unwrap();
+        let mut loaded = NodeGraph::from_description(&description).unwrap();
+        assert_eq!(loaded.get_node_ids(), remaining);
+        assert_eq!(loaded.to_description(), node_graph.to_description());
+
+        // a node added after loading gets the id it would have gotten before saving
+        let added = loaded.add_node(SphereNode::default());
+        assert!(!ids.contains(&added));
+        assert_eq!(added, node_graph.add_node(SphereNode::default()));
+    }
+
+    #[test]
+    fn test_unknown_node_type() {
+        let text = "(nodes: [(id: 0, type: \"teapot\")], connections: [])";
+        let description = GraphDescription::from_ron(text).unwrap();
+        assert!(matches!(
+            NodeGraph::from_description(&description),
+            Err(FileError::UnknownNodeType(identifier)) if identifier == "teapot"
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_node_id() {
+        let text =
+            "(nodes: [(id: 3, type: \"sphere\"), (id: 3, type: \"output\")], connections: [])";
+        let description = GraphDescription::from_ron(text).unwrap();
+        let error = NodeGraph::from_description(&description).err().unwrap();
+        assert!(matches!(error, FileError::DuplicateNode(node) if node == NodeId::from_raw(3)));
+        assert_eq!(error.to_string(), "more than one node has the id #3");
+    }
+
+    #[test]
+    fn test_groups_round_trip() {
+        let mut node_graph = crate::example().unwrap();
+        let ids = node_graph.get_node_ids();
+        let (inner, _) = node_graph.group_nodes(&ids[2..3], "scale").unwrap();
+        node_graph.group_nodes(&[ids[1], inner], "scatter").unwrap();
+
+        let description = node_graph.to_description();
+        assert_eq!(description.groups.len(), 1);
+        assert_eq!(description.groups[0].graph.groups.len(), 1);
+
+        let text = description.to_ron().unwrap();
+        let mut registry = NodeRegistry::default();
+        let mut loaded = NodeGraph::from_description_with_registry(
+            &GraphDescription::from_ron(&text).unwrap(),
+            &mut registry,
+        )
+        .unwrap();
+        assert!(registry.group("group_scatter").is_some());
+        assert!(registry.group("group_scale").is_some());
+        assert_eq!(loaded.to_description(), description);
+        assert_eq!(
+            loaded.get_output().unwrap().vertices,
+            node_graph.get_output().unwrap().vertices
+        );
+    }
+}
@@ -0,0 +1,713 @@
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics;
+use crate::error::{ConvertError, GraphError};
+use crate::parameter::ParameterValue;
+use crate::socket::Socket;
+use crate::{Connection, NodeAny, NodeGraph, NodeId, NodeSocket, Value};
+
+// a group definition shared by every instance of the group
+#[derive(Clone)]
+pub struct SharedGroup(Arc<RwLock<GroupDefinition>>);
+
+impl SharedGroup {
+    pub fn new(definition: GroupDefinition) -> Self {
+        let group = Self(Arc::new(RwLock::new(definition)));
+        let owner = WeakGroup(Arc::downgrade(&group.0));
+        group.write().graph.set_owner(owner);
+        group
+    }
+
+    // whether both are the same definition, not just definitions with the same name
+    pub fn ptr_eq(&self, other: &SharedGroup) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    // a panic while the definition is locked can't leave it half edited, so a poisoned lock is still used
+    pub fn read(&self) -> RwLockReadGuard<'_, GroupDefinition> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // instances only lock the definition while they copy it, so this doesn't wait for them to finish
+    // a group can't contain itself, adding an instance of a group to its own graph fails
+    pub fn write(&self) -> RwLockWriteGuard<'_, GroupDefinition> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// an input or output of a group, going to or coming from a socket of a node inside of it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupSocket {
+    pub name: String,
+    pub socket: NodeSocket,
+}
+
+// a graph that can be used as a single node, with some of its sockets exposed
+#[derive(Clone)]
+pub struct GroupDefinition {
+    name: String,
+    graph: NodeGraph,
+    inputs: Vec<GroupSocket>,
+    outputs: Vec<GroupSocket>,
+    // increased every time the graph is edited, so the instances know to evaluate again
+    version: u64,
+}
+
+impl GroupDefinition {
+    pub fn new(
+        name: &str,
+        graph: NodeGraph,
+        inputs: Vec<GroupSocket>,
+        outputs: Vec<GroupSocket>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            graph,
+            inputs,
+            outputs,
+            version: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // the identifier instances of the group are saved with
+    pub fn identifier(&self) -> String {
+        format!("group_{}", self.name)
+    }
+
+    pub fn graph(&self) -> &NodeGraph {
+        &self.graph
+    }
+
+    // edits the graph of the group, every instance of it is evaluated again
+    pub fn graph_mut(&mut self) -> &mut NodeGraph {
+        self.version += 1;
+        &mut self.graph
+    }
+
+    pub fn inputs(&self) -> &[GroupSocket] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[GroupSocket] {
+        &self.outputs
+    }
+
+    // changes when the group or any group nested in it is edited
+    pub fn version(&self) -> u64 {
+        self.version
+            + self
+                .graph
+                .get_nodes()
+                .iter()
+                .map(|node| node.version())
+                .sum::<u64>()
+    }
+
+    // evaluates the graph with the values of the inputs of the group, returning the values of its outputs
+    fn evaluate(&mut self, inputs: Vec<Value>) -> Result<Vec<Value>, GraphError> {
+        for (input, value) in self.inputs.iter().zip(inputs) {
+            self.graph.set_external_input(&input.socket, value);
+        }
+
+        let mut values = Vec::new();
+        for output in self.outputs.iter() {
+            let node = output.socket.node();
+            let socket = output.socket.socket();
+            let value = self
+                .graph
+                .get_output_of_node(node)?
+                .get(socket)
+                .cloned()
+                .ok_or(GraphError::SocketOutOfRange { node, socket })?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn input_sockets(&self) -> Vec<Socket> {
+        self.inputs
+            .iter()
+            .map(|input| {
+                let socket = self
+                    .graph
+                    .get_node(input.socket.node())
+                    .and_then(|node| node.input_sockets().into_iter().nth(input.socket.socket()));
+                let Some(mut socket) = socket else {
+                    return removed_socket(&input.name);
+                };
+                socket.name = input.name.clone();
+                socket.default = self.graph.get_input_default(&input.socket);
+                socket
+            })
+            .collect()
+    }
+
+    fn output_sockets(&self) -> Vec<Socket> {
+        self.outputs
+            .iter()
+            .map(|output| {
+                let socket = self.graph.get_node(output.socket.node()).and_then(|node| {
+                    node.output_sockets()
+                        .into_iter()
+                        .nth(output.socket.socket())
+                });
+                let Some(mut socket) = socket else {
+                    return removed_socket(&output.name);
+                };
+                socket.name = output.name.clone();
+                socket
+            })
+            .collect()
+    }
+}
+
+// the type of an exposed socket whose node was removed from the group
+// nothing converts to or from it, so the connections of the socket fail with a type mismatch
+struct RemovedSocket;
+
+// an exposed socket keeps its place when its node is removed, so the sockets after it keep their connections
+fn removed_socket(name: &str) -> Socket {
+    Socket {
+        name: name.to_string(),
+        description: "The node of this socket was removed from the group".to_string(),
+        type_id: TypeId::of::<RemovedSocket>(),
+        type_name: "removed".to_string(),
+        default: None,
+        variadic: false,
+    }
+}
+
+// the group a graph belongs to, weak so the graph doesn't keep its own group alive
+#[derive(Clone)]
+pub(crate) struct WeakGroup(Weak<RwLock<GroupDefinition>>);
+
+// an instance of a group, evaluating a copy of the graph of the group
+#[derive(Clone)]
+pub struct GroupNode {
+    definition: SharedGroup,
+    name: String,
+    identifier: String,
+}
+
+impl GroupNode {
+    pub fn new(definition: SharedGroup) -> Self {
+        let (name, identifier) = {
+            let definition = definition.read();
+            (definition.name().to_string(), definition.identifier())
+        };
+        Self {
+            definition,
+            name,
+            identifier,
+        }
+    }
+
+    pub fn definition(&self) -> &SharedGroup {
+        &self.definition
+    }
+
+    // whether the group is `owner`, or has an instance of it somewhere inside
+    // the definitions before `owner` are read, so `owner` itself can be locked for editing
+    pub(crate) fn contains(&self, owner: &WeakGroup) -> bool {
+        let Some(owner) = owner.0.upgrade() else {
+            return false;
+        };
+        let owner = SharedGroup(owner);
+        let mut stack = vec![self.definition.clone()];
+        while let Some(definition) = stack.pop() {
+            if definition.ptr_eq(&owner) {
+                return true;
+            }
+            let definition = definition.read();
+            stack.extend(
+                definition
+                    .graph
+                    .get_nodes()
+                    .iter()
+                    .filter_map(|node| node.as_group())
+                    .map(|group| group.definition.clone()),
+            );
+        }
+        false
+    }
+}
+
+impl NodeAny for GroupNode {
+    fn operation(&self, input: Vec<Value>) -> Result<Vec<Value>, ConvertError> {
+        // a copy of the definition is evaluated so the lock isn't held while the nodes inside run
+        // a thread waiting for a parallel node in here can pick up another instance of the group,
+        // which would wait for the lock forever if it were still held
+        // the copy starts without the values cached by other instances, so every node inside runs
+        let mut definition = self.definition.read().clone();
+        let result = definition.evaluate(input);
+
+        // the messages of the nodes inside are emitted again as messages of the group
+        for diagnostic in definition.graph.get_diagnostics() {
+            let name = definition
+                .graph
+                .get_node(diagnostic.node)
+                .map(|node| node.name().to_string())
+                .unwrap_or_default();
+            diagnostics::emit(
+                diagnostic.level,
+                format!("{} {}: {}", name, diagnostic.node, diagnostic.message),
+            );
+        }
+
+        result.map_err(|error| ConvertError::Failed {
+            message: definition.graph.describe_error(&error),
+        })
+    }
+
+    fn needed_types_input(&self) -> Vec<TypeId> {
+        self.input_sockets()
+            .iter()
+            .map(|socket| socket.type_id)
+            .collect()
+    }
+
+    fn needed_types_output(&self) -> Vec<TypeId> {
+        self.output_sockets()
+            .iter()
+            .map(|socket| socket.type_id)
+            .collect()
+    }
+
+    fn variadic_inputs(&self) -> Vec<bool> {
+        self.input_sockets()
+            .iter()
+            .map(|socket| socket.variadic)
+            .collect()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        "A group of nodes"
+    }
+
+    fn input_sockets(&self) -> Vec<Socket> {
+        self.definition.read().input_sockets()
+    }
+
+    fn output_sockets(&self) -> Vec<Socket> {
+        self.definition.read().output_sockets()
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        Vec::new()
+    }
+
+    fn set_parameter(&mut self, _name: &str, _value: ParameterValue) -> bool {
+        false
+    }
+
+    fn node_type_id(&self) -> TypeId {
+        TypeId::of::<GroupNode>()
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn version(&self) -> u64 {
+        // while the group is being edited its version can't be read
+        match self.definition.0.try_read() {
+            Ok(definition) => definition.version(),
+            Err(_) => 0,
+        }
+    }
+
+    fn as_group(&self) -> Option<&GroupNode> {
+        Some(self)
+    }
+
+    fn clone_node(&self) -> Box<dyn NodeAny> {
+        Box::new(self.clone())
+    }
+}
+
+impl NodeGraph {
+    // moves the nodes into a new group and puts an instance of it in their place
+    // connections going into or out of the selection become the inputs and outputs of the group
+    pub fn group_nodes(
+        &mut self,
+        nodes: &[NodeId],
+        name: &str,
+    ) -> Result<(NodeId, SharedGroup), GraphError> {
+        if nodes.is_empty() {
+            return Err(GraphError::EmptyGroup);
+        }
+        for &node in nodes {
+            match self.get_node(node) {
+                None => return Err(GraphError::NodeNotFound { node }),
+                Some(node_any) if node_any.is_output() => {
+                    return Err(GraphError::GroupedOutput { node })
+                }
+                Some(_) => {}
+            }
+        }
+        if self.uses_group_name(name) {
+            return Err(GraphError::DuplicateGroup {
+                name: name.to_string(),
+            });
+        }
+        let selected: HashSet<NodeId> = nodes.iter().copied().collect();
+
+        let mut inner_connections: Vec<Connection> = Vec::new();
+        let mut inputs: Vec<GroupSocket> = Vec::new();
+        let mut outputs: Vec<GroupSocket> = Vec::new();
+        // the outside ends of the connections, with the socket of the group they go through
+        let mut outer_inputs: Vec<(NodeSocket, usize)> = Vec::new();
+        let mut outer_outputs: Vec<(usize, NodeSocket)> = Vec::new();
+
+        for connection in self.get_connections() {
+            let from = connection.from();
+            let to = connection.to();
+            match (
+                selected.contains(&from.node()),
+                selected.contains(&to.node()),
+            ) {
+                (true, true) => inner_connections.push(connection),
+                (false, true) => {
+                    let index = self.expose(&mut inputs, &to, true);
+                    outer_inputs.push((from, index));
+                }
+                (true, false) => {
+                    let index = self.expose(&mut outputs, &from, false);
+                    outer_outputs.push((index, to));
+                }
+                (false, false) => {}
+            }
+        }
+
+        // a node outside of the selection between two selected nodes would make the group depend on itself
+        // everything is checked before the nodes are moved, so a failure leaves the graph as it was
+        for (from, socket) in outer_inputs.iter() {
+            if selected
+                .iter()
+                .any(|&node| self.depends_on(from.node(), node))
+            {
+                return Err(GraphError::Cycle {
+                    from: from.clone(),
+                    to: inputs[*socket].socket.clone(),
+                });
+            }
+        }
+
+        // connections inside of the group can rely on the conversions of the graph
+        let mut graph = NodeGraph::new();
+        graph.set_conversions(self.conversions().clone());
+        for &node in selected.iter() {
+            let defaults = self.get_input_defaults(node);
+            graph.insert_node(node, self.remove_node(node)?);
+            for (socket, value) in defaults {
+                graph.set_input_default(&NodeSocket::new(node, socket), value)?;
+            }
+        }
+        for connection in inner_connections {
+            graph.connect(connection.from(), connection.to())?;
+        }
+
+        let definition = SharedGroup::new(GroupDefinition::new(name, graph, inputs, outputs));
+        let group = self.add_dyn_node(Box::new(GroupNode::new(definition.clone())))?;
+        for (from, socket) in outer_inputs {
+            self.connect(from, NodeSocket::new(group, socket))?;
+        }
+        for (socket, to) in outer_outputs {
+            self.connect(NodeSocket::new(group, socket), to)?;
+        }
+
+        Ok((group, definition))
+    }
+
+    // whether a group with this name is used in the graph, or in a group nested in it
+    fn uses_group_name(&self, name: &str) -> bool {
+        self.get_nodes().iter().any(|node| {
+            let Some(group) = node.as_group() else {
+                return false;
+            };
+            let definition = group.definition().read();
+            definition.name() == name || definition.graph().uses_group_name(name)
+        })
+    }
+
+    // the index of the group socket exposing a socket, added if it isn't exposed yet
+    fn expose(&self, sockets: &mut Vec<GroupSocket>, socket: &NodeSocket, input: bool) -> usize {
+        if let Some(index) = sockets.iter().position(|exposed| &exposed.socket == socket) {
+            return index;
+        }
+
+        let node = self.get_node(socket.node());
+        let name = node
+            .and_then(|node| {
+                let sockets = if input {
+                    node.input_sockets()
+                } else {
+                    node.output_sockets()
+                };
+                sockets.into_iter().nth(socket.socket())
+            })
+            .map(|socket| socket.name)
+            .unwrap_or_default();
+        sockets.push(GroupSocket {
+            name,
+            socket: socket.clone(),
+        });
+        sockets.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::Conversions;
+    use crate::nodes::{OutputNode, ScaleInstanceNode, SphereNode, ValueNode};
+    use crate::{Node, NodeError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::OnceLock;
+
+    #[test]
+    fn test_group_nodes() {
+        let mut node_graph = crate::example().unwrap();
+        let expected = node_graph.get_output().unwrap().vertices;
+
+        // instantiate, scale and merge
+        let ids = node_graph.get_node_ids();
+        let (group, definition) = node_graph.group_nodes(&ids[1..4], "scatter").unwrap();
+
+        assert_eq!(node_graph.get_node_ids().len(), 3);
+        let group_node = node_graph.get_node(group).unwrap();
+        assert_eq!(group_node.identifier(), "group_scatter");
+        assert_eq!(group_node.input_sockets().len(), 2);
+        assert_eq!(group_node.output_sockets().len(), 1);
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+
+        // a second instance shares the definition
+        let sphere = NodeSocket::new(ids[0], 0);
+        let other = node_graph
+            .add_dyn_node(Box::new(GroupNode::new(definition.clone())))
+            .unwrap();
+        node_graph
+            .connect(sphere.clone(), NodeSocket::new(other, 0))
+            .unwrap();
+        node_graph
+            .connect(sphere, NodeSocket::new(other, 1))
+            .unwrap();
+        assert_eq!(node_graph.get_output_of_node(other).unwrap().len(), 1);
+
+        // editing the definition updates the instances, the grouped nodes keep their ids
+        let scale = ids[2];
+        definition
+            .write()
+            .graph_mut()
+            .set_input_default(&NodeSocket::new(scale, 1), ParameterValue::Float(0.2))
+            .unwrap();
+        assert_ne!(node_graph.get_output().unwrap().vertices, expected);
+    }
+
+    #[test]
+    fn test_remove_exposed_node() {
+        let mut node_graph = crate::example().unwrap();
+        let ids = node_graph.get_node_ids();
+        let (group, definition) = node_graph.group_nodes(&ids[1..4], "scatter").unwrap();
+
+        // the instantiate node backs both inputs of the group, they stay as removed sockets
+        definition.write().graph_mut().remove_node(ids[1]).unwrap();
+        let group_node = node_graph.get_node(group).unwrap();
+        assert_eq!(group_node.input_sockets().len(), 2);
+        assert_eq!(group_node.input_sockets()[0].type_name, "removed");
+        assert_eq!(group_node.output_sockets()[0].name, "Model");
+
+        assert!(matches!(
+            node_graph.get_output(),
+            Err(GraphError::TypeMismatch { to, .. }) if to.node() == group
+        ));
+    }
+
+    #[test]
+    fn test_group_nodes_fails_unchanged() {
+        let mut node_graph = crate::example().unwrap();
+        let expected = node_graph.get_output().unwrap().vertices;
+        let ids = node_graph.get_node_ids();
+
+        // the instantiate node is between the sphere and the scale node
+        assert!(matches!(
+            node_graph.group_nodes(&[ids[0], ids[2]], "loop"),
+            Err(GraphError::Cycle { .. })
+        ));
+        assert_eq!(node_graph.get_node_ids(), ids);
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+
+        assert_eq!(
+            node_graph.group_nodes(&[], "empty").err(),
+            Some(GraphError::EmptyGroup)
+        );
+        assert_eq!(
+            node_graph.group_nodes(&ids[3..5], "with_output").err(),
+            Some(GraphError::GroupedOutput { node: ids[4] })
+        );
+        assert_eq!(node_graph.get_node_ids(), ids);
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+
+        node_graph.group_nodes(&ids[1..2], "scatter").unwrap();
+        assert_eq!(
+            node_graph.group_nodes(&ids[2..3], "scatter").err(),
+            Some(GraphError::DuplicateGroup {
+                name: "scatter".to_string()
+            })
+        );
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+    }
+
+    #[test]
+    fn test_group_keeps_conversions() {
+        let mut conversions = Conversions::default();
+        conversions.register(|big: &bool| if *big { 2.0_f32 } else { 1.0 });
+        let mut node_graph = NodeGraph::new();
+        node_graph.set_conversions(conversions);
+        let sphere = node_graph.add_node(SphereNode::default());
+        let big = node_graph.add_node(ValueNode::new(true));
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(big, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(output, 0))
+            .unwrap();
+        let expected = node_graph.get_output().unwrap().vertices;
+
+        node_graph.group_nodes(&[big, scale], "big").unwrap();
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+    }
+
+    #[test]
+    fn test_group_diagnostics() {
+        let mut node_graph = crate::example().unwrap();
+        let ids = node_graph.get_node_ids();
+        let (group, _) = node_graph.group_nodes(&ids[1..4], "scatter").unwrap();
+        node_graph.get_output().unwrap();
+
+        let diagnostics = node_graph.get_node_diagnostics(group);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].node, group);
+        assert!(diagnostics[0].message.contains("spawning"));
+    }
+
+    #[test]
+    fn test_recursive_group() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(output, 0))
+            .unwrap();
+        let (inner, inner_definition) = node_graph.group_nodes(&[scale], "inner").unwrap();
+        let (_, outer_definition) = node_graph.group_nodes(&[inner], "outer").unwrap();
+
+        // directly, and through the outer group holding an instance of the inner one
+        for definition in [&inner_definition, &outer_definition] {
+            let name = definition.read().name().to_string();
+            let instance = Box::new(GroupNode::new(definition.clone()));
+            let added = inner_definition.write().graph_mut().add_dyn_node(instance);
+            assert_eq!(added.err(), Some(GraphError::RecursiveGroup { name }));
+        }
+        assert!(node_graph.get_output().is_ok());
+    }
+
+    // records whether the group it is in could be edited while it ran
+    #[derive(Clone)]
+    struct LockCheckNode {
+        definition: Arc<OnceLock<SharedGroup>>,
+        unlocked: Arc<AtomicUsize>,
+    }
+
+    impl Node<(), (f32,)> for LockCheckNode {
+        fn try_operation(&self, _: ()) -> Result<(f32,), NodeError> {
+            if let Some(definition) = self.definition.get() {
+                if definition.0.try_write().is_ok() {
+                    self.unlocked.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            Ok((1.0,))
+        }
+
+        fn identifier() -> String {
+            "lock_check".to_string()
+        }
+    }
+
+    #[test]
+    fn test_group_not_locked_while_evaluated() {
+        let check = LockCheckNode {
+            definition: Arc::new(OnceLock::new()),
+            unlocked: Arc::new(AtomicUsize::new(0)),
+        };
+        let mut graph = NodeGraph::new();
+        let value = graph.add_node(check.clone());
+        let outputs = vec![GroupSocket {
+            name: "Value".to_string(),
+            socket: NodeSocket::new(value, 0),
+        }];
+        let definition = SharedGroup::new(GroupDefinition::new("check", graph, vec![], outputs));
+        check.definition.set(definition.clone()).ok().unwrap();
+
+        // the definition could be locked by another instance while the nodes inside run
+        let mut node_graph = NodeGraph::new();
+        for _ in 0..2 {
+            let instance = Box::new(GroupNode::new(definition.clone()));
+            let instance = node_graph.add_dyn_node(instance).unwrap();
+            assert_eq!(node_graph.get_output_of_node(instance).unwrap().len(), 1);
+        }
+        assert_eq!(check.unlocked.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let amount = node_graph.add_node(ValueNode::new(2.0_f32));
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(amount, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(output, 0))
+            .unwrap();
+        let expected = node_graph.get_output().unwrap().vertices;
+
+        let (inner, _) = node_graph.group_nodes(&[scale], "scale").unwrap();
+        let (outer, _) = node_graph.group_nodes(&[amount, inner], "amount").unwrap();
+
+        let outer_node = node_graph.get_node(outer).unwrap();
+        assert_eq!(outer_node.input_sockets().len(), 1);
+        assert_eq!(outer_node.input_sockets()[0].name, "Instances");
+        assert_eq!(node_graph.get_output().unwrap().vertices, expected);
+    }
+}
@@ -0,0 +1,71 @@
+// lets the code generated by the node macro refer to this crate by name
+extern crate self as mesh_mancer;
+
+pub mod conversion;
+pub mod diagnostics;
+mod error;
+pub mod geometry;
+pub mod graph_file;
+pub mod group;
+pub mod macros;
+mod model;
+mod node;
+pub mod nodes;
+pub mod overrides;
+pub mod parameter;
+pub mod profile;
+pub mod registry;
+#[cfg(feature = "editor")]
+pub mod renderer;
+pub mod socket;
+
+pub use error::{ConvertError, FileError, GraphError, NodeError, OverrideError};
+pub use mesh_mancer_macros::node;
+pub use model::Model;
+pub use node::{box_node, Connection, Node, NodeAny, NodeGraph, NodeId, NodeSocket, Value};
+
+use nodes::{InstatiateOnPointsNode, MergeNode, OutputNode, ScaleInstanceNode, SphereNode};
+use parameter::ParameterValue;
+
+// the graph the editor starts with, a sphere with a small sphere on each of its vertices
+pub fn example() -> Result<NodeGraph, GraphError> {
+    let mut node_graph = NodeGraph::new();
+
+    let sphere_node_index = node_graph.add_node(SphereNode::default());
+    let instatiate_node_index = node_graph.add_node(InstatiateOnPointsNode {});
+    let scale_node_index = node_graph.add_node(ScaleInstanceNode {});
+    let merge_node_index = node_graph.add_node(MergeNode {});
+    let output_node_index = node_graph.add_node(OutputNode::default());
+
+    node_graph.connect(
+        NodeSocket::new(sphere_node_index, 0),
+        NodeSocket::new(instatiate_node_index, 0),
+    )?;
+
+    node_graph.connect(
+        NodeSocket::new(sphere_node_index, 0),
+        NodeSocket::new(instatiate_node_index, 1),
+    )?;
+
+    node_graph.connect(
+        NodeSocket::new(instatiate_node_index, 0),
+        NodeSocket::new(scale_node_index, 0),
+    )?;
+
+    node_graph.set_input_default(
+        &NodeSocket::new(scale_node_index, 1),
+        ParameterValue::Float(0.1),
+    )?;
+
+    node_graph.connect(
+        NodeSocket::new(scale_node_index, 0),
+        NodeSocket::new(merge_node_index, 0),
+    )?;
+
+    node_graph.connect(
+        NodeSocket::new(merge_node_index, 0),
+        NodeSocket::new(output_node_index, 0),
+    )?;
+
+    Ok(node_graph)
+}
@@ -1,59 +1,74 @@
 use std::any::{Any, TypeId};
 
+use crate::error::ConvertError;
+use crate::socket::SocketType;
+
 pub trait InputOrOutput {
     type T;
-    fn convert(items: Vec<&dyn Any>) -> Self::T;
-    fn convert_output(item: &Self::T) -> Vec<Box<dyn Any>>;
+    fn convert(items: Vec<&dyn Any>) -> Result<Self::T, ConvertError>;
     fn needed_types() -> Vec<TypeId>;
+    fn type_names() -> Vec<&'static str>;
+    // which of the sockets take any number of connections
+    fn variadic() -> Vec<bool>;
+}
+
+// the output sockets of a node, every output is a single value so none of them can be variadic
+pub trait Output: InputOrOutput {
+    // moves the values out of the node, so outputs don't need to be copied
+    fn convert_output(item: Self::T) -> Vec<Box<dyn Any + Send + Sync>>;
 }
 
 macro_rules! tuple_to_vec {
     ($($elem:expr),*) => {
         {
-            let mut vec: Vec<Box<dyn Any>> = Vec::new();
-            $(
-                vec.push(Box::new($elem) as Box<dyn Any>);
-            )*
+            let vec: Vec<Box<dyn Any + Send + Sync>> = vec![$($elem),*];
             vec
         }
     };
 }
 
 // make a macro that generates the impl Input to turn Vec<dyn Any> into (A, B, C, D)
-// make sure T1, T2, T3, T4 are Clone and 'static
+// with no types it implements `()`, the input of nodes without input sockets
+// make sure T1, T2, T3, T4 are socket types, so either Clone and 'static or Variadic
 macro_rules! impl_input {
     ($($t:ident),*) => {
         #[allow(non_snake_case)]
-        impl<$($t: Clone + 'static),*> InputOrOutput for ($($t,)*) {
+        impl<$($t: SocketType),*> InputOrOutput for ($($t,)*) {
             type T = ($($t,)*);
-            fn convert(items: Vec<&dyn Any>) -> Self::T {
-                let mut items = items;
-                let new_item = Box::new(()) as Box<dyn Any>;
-                if TypeId::of::<Self::T>() == TypeId::of::<((),)>() {
-                    items.push(&*new_item as &dyn Any);
-                }
-
+            // a node without inputs ignores the items, so `socket` and `items` are not always used
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn convert(items: Vec<&dyn Any>) -> Result<Self::T, ConvertError> {
                 let mut items = items.into_iter();
+                let mut socket = 0;
 
-                ($($t::clone(items.next().expect("Not enough elements!").downcast_ref::<$t>().expect("Wrong type!")),)*)
-            }
-            fn convert_output(($($t,)*): &Self::T) -> Vec<Box<dyn Any>> {
-                tuple_to_vec!($($t.clone()),*)
+                Ok(($({
+                    let item = items.next().ok_or(ConvertError::MissingInput { socket })?;
+                    let item = $t::from_any(item).ok_or(ConvertError::DowncastFailed { socket })?;
+                    socket += 1;
+                    item
+                },)*))
             }
             fn needed_types() -> Vec<TypeId> {
-                // if T is (), then we don't need any types
-                // this is not a good solution, but rust doesn't allow negative trait bounds
-                // which is needed for a specific implementation of () only
-                if TypeId::of::<Self::T>() == TypeId::of::<((),)>() {
-                    return vec![];
-                }
+                vec![$($t::value_type_id()),*]
+            }
+            fn type_names() -> Vec<&'static str> {
+                vec![$($t::value_type_name()),*]
+            }
+            fn variadic() -> Vec<bool> {
+                vec![$($t::VARIADIC),*]
+            }
+        }
 
-                vec![$(TypeId::of::<$t>()),*]
+        #[allow(non_snake_case)]
+        impl<$($t: Clone + Send + Sync + 'static),*> Output for ($($t,)*) {
+            fn convert_output(($($t,)*): Self::T) -> Vec<Box<dyn Any + Send + Sync>> {
+                tuple_to_vec!($(Box::new($t)),*)
             }
         }
     };
 }
 
+impl_input!();
 impl_input!(T1);
 impl_input!(T1, T2);
 impl_input!(T1, T2, T3);
@@ -75,3 +90,16 @@ impl_input!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T1
 impl_input!(
     T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_inputs() {
+        assert!(<()>::needed_types().is_empty());
+        assert!(<()>::type_names().is_empty());
+        assert!(<()>::convert(vec![]).is_ok());
+        assert!(<()>::convert_output(()).is_empty());
+    }
+}
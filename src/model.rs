@@ -0,0 +1,275 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use three_d::*;
+
+// the buffers are shared between clones, they are only copied when a clone changes them
+#[derive(Clone)]
+pub struct Model {
+    pub(crate) vertices: Arc<Vec<Vector3<f32>>>,
+    pub(crate) indices: Arc<Vec<u32>>,
+    pub(crate) normals: Arc<Vec<Vector3<f32>>>,
+    pub(crate) transform: Matrix4<f32>,
+    normals_calculated: bool,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Model {
+    pub fn new() -> Self {
+        Self {
+            vertices: Arc::new(Vec::new()),
+            indices: Arc::new(Vec::new()),
+            normals: Arc::new(Vec::new()),
+            transform: Matrix4::identity(),
+            normals_calculated: false,
+        }
+    }
+
+    pub fn vertices(&self) -> &[Vector3<f32>] {
+        &self.vertices
+    }
+
+    // every three indices are the vertices of a triangle
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn add_vertex(&mut self, x: f32, y: f32, z: f32) {
+        Arc::make_mut(&mut self.vertices).push(Vector3::new(x, y, z));
+        self.normals_calculated = false;
+    }
+
+    pub fn add_index(&mut self, a: u32, b: u32, c: u32) {
+        Arc::make_mut(&mut self.indices).extend([a, b, c]);
+        self.normals_calculated = false;
+    }
+
+    pub fn seperate_parts(&self) -> Vec<Model> {
+        let mut models = Vec::new();
+        let mut sets: Vec<(HashSet<u32>, Vec<u32>)> = vec![];
+
+        for index in self.indices.chunks(3) {
+            let mut found = false;
+            'set_loop: for set in sets.iter_mut() {
+                for i in index.iter() {
+                    if set.0.contains(i) {
+                        set.0.insert(index[0]);
+                        set.0.insert(index[1]);
+                        set.0.insert(index[2]);
+                        set.1.extend(index[0..3].to_vec());
+                        found = true;
+                        break 'set_loop;
+                    }
+                }
+            }
+            if !found {
+                let mut set = HashSet::new();
+                set.extend(index[0..3].iter());
+                sets.push((set, index[0..3].to_vec()));
+            }
+        }
+
+        let mut index_to_remove = None;
+        // merge the sets
+        'outer_loop: loop {
+            if let Some(index) = index_to_remove {
+                sets.remove(index);
+            }
+            let set_clone = sets.clone();
+            for (mutable_set_index, set) in sets.iter_mut().enumerate() {
+                for (i, other_set) in set_clone.iter().enumerate() {
+                    if mutable_set_index == i {
+                        continue;
+                    }
+                    if set.0.intersection(&other_set.0).count() > 0 {
+                        set.0.extend(other_set.0.iter());
+                        set.1.extend(other_set.1.iter());
+                        index_to_remove = Some(i);
+                        // restart the search as the sets have changed
+                        continue 'outer_loop;
+                    }
+                }
+            }
+            break;
+        }
+
+        let mut global_to_local_indices = vec![];
+        for set in sets.iter() {
+            let mut global_to_local_index = HashMap::new();
+            let sorted = set.0.iter().collect::<Vec<&u32>>();
+
+            for (i, index) in sorted.iter().enumerate() {
+                global_to_local_index.insert(*index, i as u32);
+            }
+            global_to_local_indices.push(global_to_local_index);
+        }
+
+        for (i, set) in sets.iter().enumerate() {
+            let global_to_local_index = &global_to_local_indices[i];
+
+            let mut model = Model::new();
+            for index in set.1.chunks(3) {
+                model.add_index(
+                    global_to_local_index[&index[0]],
+                    global_to_local_index[&index[1]],
+                    global_to_local_index[&index[2]],
+                );
+            }
+            for index in set.0.iter() {
+                model.add_vertex(
+                    self.vertices[*index as usize].x,
+                    self.vertices[*index as usize].y,
+                    self.vertices[*index as usize].z,
+                );
+            }
+
+            model.set_transform(self.transform);
+
+            models.push(model);
+        }
+
+        models
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_gm_single(&mut self, context: &Context) -> Gm<Mesh, PhysicalMaterial> {
+        if !self.normals_calculated {
+            self.auto_generate_normals();
+        }
+
+        let mesh = Mesh::new(
+            context,
+            &CpuMesh {
+                positions: Positions::F32(self.vertices.to_vec()),
+                indices: Indices::U32(self.indices.to_vec()),
+                normals: Some(self.normals.to_vec()),
+                uvs: None,
+                colors: None,
+                tangents: None,
+            },
+        );
+
+        let mut gm = Gm::new(
+            mesh,
+            PhysicalMaterial::new_opaque(
+                context,
+                &CpuMaterial {
+                    albedo: Srgba::new(200, 200, 200, 255),
+                    ..Default::default()
+                },
+            ),
+        );
+
+        gm.set_transformation(self.transform);
+
+        gm
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn into_gms(&mut self, context: &Context) -> Vec<Gm<Mesh, PhysicalMaterial>> {
+        let mut gms = Vec::new();
+
+        for mut model in self.seperate_parts() {
+            gms.push(model.into_gm_single(context));
+        }
+
+        gms
+    }
+
+    pub fn auto_generate_normals(&mut self) {
+        let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); self.vertices.len()];
+        for i in 0..self.indices.len() / 3 {
+            let a = self.indices[i * 3] as usize;
+            let b = self.indices[i * 3 + 1] as usize;
+            let c = self.indices[i * 3 + 2] as usize;
+            let normal = (self.vertices[b] - self.vertices[a])
+                .cross(self.vertices[c] - self.vertices[a])
+                .normalize();
+            normals[a] += normal;
+            normals[b] += normal;
+            normals[c] += normal;
+        }
+        self.normals = Arc::new(normals);
+        self.normals_calculated = true;
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix4<f32>) {
+        self.transform = transform;
+    }
+
+    pub fn transform(&self) -> Matrix4<f32> {
+        self.transform
+    }
+
+    // the model as a wavefront obj file, with the vertices moved by the transform
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+        for vertex in self.vertices.iter() {
+            let point = self
+                .transform
+                .transform_point(Point3::new(vertex.x, vertex.y, vertex.z));
+            let _ = writeln!(obj, "v {} {} {}", point.x, point.y, point.z);
+        }
+        // the vertices of a face are counted from one
+        for triangle in self.indices.chunks_exact(3) {
+            let _ = writeln!(
+                obj,
+                "f {} {} {}",
+                triangle[0] + 1,
+                triangle[1] + 1,
+                triangle[2] + 1
+            );
+        }
+        obj
+    }
+
+    pub fn save_obj(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_obj())
+    }
+
+    pub fn merge(&mut self, other: &Model) {
+        let offset = self.vertices.len() as u32;
+        let vertices = Arc::make_mut(&mut self.vertices);
+        for vertex in other.vertices.iter() {
+            let mut point = Point3 {
+                x: vertex.x,
+                y: vertex.y,
+                z: vertex.z,
+            };
+
+            point = other.transform.transform_point(point);
+
+            vertices.push(Vector3::new(point.x, point.y, point.z));
+        }
+        Arc::make_mut(&mut self.indices).extend(other.indices.iter().map(|i| i + offset));
+        Arc::make_mut(&mut self.normals).extend(other.normals.iter());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_obj() {
+        let mut model = Model::new();
+        model.add_vertex(0.0, 0.0, 0.0);
+        model.add_vertex(1.0, 0.0, 0.0);
+        model.add_vertex(0.0, 1.0, 0.0);
+        model.add_index(0, 1, 2);
+        model.set_transform(Matrix4::from_translation(Vector3::new(0.0, 0.0, 2.0)));
+
+        assert_eq!(model.to_obj(), "v 0 0 2\nv 1 0 2\nv 0 1 2\nf 1 2 3\n");
+    }
+}
@@ -1,21 +1,58 @@
 use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::conversion::Conversions;
+use crate::diagnostics::{self, Diagnostic};
+use crate::error::{ConvertError, GraphError, NodeError};
+use crate::group::{GroupNode, WeakGroup};
+use crate::macros::{InputOrOutput, Output};
+use crate::parameter::ParameterValue;
+use crate::profile::ProfileReport;
+use crate::socket::short_type_name;
+use crate::socket::{Socket, SocketDescriptor};
 use crate::Model;
-use crate::{macros::InputOrOutput, OutputNode};
-use three_d::{CpuMesh, Matrix4, Vector3};
 
-#[derive(Clone)]
+// identifies a node in a graph, ids are never reused so they stay valid when nodes are removed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(u64);
+
+impl NodeId {
+    // the id with this number, like the number a node is shown with in the editor
+    pub fn from_raw(index: u64) -> Self {
+        Self(index)
+    }
+
+    pub fn index(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NodeSocket {
-    node: usize,
+    node: NodeId,
     socket: usize,
 }
 
 impl NodeSocket {
-    pub fn new(node: usize, socket: usize) -> Self {
+    pub fn new(node: NodeId, socket: usize) -> Self {
         Self { node, socket }
     }
 
-    pub fn node(&self) -> usize {
+    pub fn node(&self) -> NodeId {
         self.node
     }
 
@@ -44,163 +81,1107 @@ impl Connection {
     }
 }
 
+// a value flowing between nodes, shared between the cache and the nodes using it
+pub type Value = Arc<dyn Any + Send + Sync>;
+
+#[derive(Clone)]
 struct NodeGraphElement {
     node: Box<dyn NodeAny>,
     inputs: Vec<Connection>,
     outputs: Vec<Connection>,
+    // the outputs of the last evaluation, None if the node needs to be evaluated again
+    cache: Option<Vec<Value>>,
+    // values set for input sockets, replacing the defaults the node gives them
+    defaults: BTreeMap<usize, ParameterValue>,
+    // values given to unconnected input sockets from outside of the graph
+    external_inputs: BTreeMap<usize, Value>,
+    // the version of the node the cache was made with
+    version: u64,
+    // why the last evaluation of the node failed
+    error: Option<GraphError>,
+    // the messages the node emitted the last time it ran
+    diagnostics: Vec<Diagnostic>,
 }
 
+// what went wrong while evaluating a graph
+#[derive(Default)]
+struct Evaluation {
+    // the nodes that failed or were skipped because a node before them failed
+    failed: HashSet<NodeId>,
+    // the error of the first node that failed, with the position of the node in evaluation order
+    // serial and parallel evaluations run the nodes in different orders but return the same error
+    first_error: Option<(usize, GraphError)>,
+}
+
+// the result of running a node, with what was recorded while it ran
+struct NodeRun {
+    result: Result<Vec<Value>, GraphError>,
+    time: Duration,
+    diagnostics: Vec<Diagnostic>,
+}
+
+// a copy of a graph has copies of its nodes, the definitions of groups in it are shared
+#[derive(Clone)]
 pub struct NodeGraph {
-    // an index to the output node
-    output_node: Option<usize>,
-    nodes_elements: Vec<NodeGraphElement>,
+    nodes_elements: BTreeMap<NodeId, NodeGraphElement>,
+    // the id given to the next node added
+    next_id: u64,
+    // used when an output is connected to an input of another type
+    conversions: Conversions,
+    // whether independent nodes are evaluated at the same time
+    parallel: bool,
+    // the statistics of the evaluated nodes, None unless profiling is turned on
+    profile: Option<ProfileReport>,
+    // the group this is the graph of, so an instance of the group can't be added to it
+    owner: Option<WeakGroup>,
+}
+
+impl Default for NodeGraph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl NodeGraph {
     pub fn new() -> Self {
         Self {
-            nodes_elements: Vec::new(),
-            output_node: None,
+            nodes_elements: BTreeMap::new(),
+            next_id: 0,
+            conversions: Conversions::default(),
+            parallel: true,
+            profile: None,
+            owner: None,
         }
     }
 
-    pub fn add_node<N, I, O>(&mut self, node: N) -> usize
+    // evaluates the nodes one at a time when false, the results and errors are the same either way
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    // records how long every node takes to run, how often it runs and how big its outputs are
+    pub fn set_profiling(&mut self, profiling: bool) {
+        match (profiling, &self.profile) {
+            (true, None) => self.profile = Some(ProfileReport::default()),
+            (false, _) => self.profile = None,
+            _ => {}
+        }
+    }
+
+    // what was recorded since profiling was turned on, None if it is off
+    pub fn profile_report(&self) -> Option<&ProfileReport> {
+        self.profile.as_ref()
+    }
+
+    // starts the recording over, keeping profiling on
+    pub fn reset_profile(&mut self) {
+        if let Some(profile) = self.profile.as_mut() {
+            *profile = ProfileReport::default();
+        }
+    }
+
+    // replaces the conversions used between sockets of different types
+    pub fn set_conversions(&mut self, conversions: Conversions) {
+        self.conversions = conversions;
+        self.nodes_elements
+            .values_mut()
+            .for_each(|element| element.cache = None);
+    }
+
+    pub fn conversions(&self) -> &Conversions {
+        &self.conversions
+    }
+
+    // whether the value going through a connection is converted to another type
+    pub fn is_converted(&self, connection: &Connection) -> bool {
+        match (
+            self.output_type(&connection.from),
+            self.input_type(&connection.to),
+        ) {
+            (Some(from), Some(to)) => from != to,
+            _ => false,
+        }
+    }
+
+    fn output_type(&self, socket: &NodeSocket) -> Option<TypeId> {
+        let element = self.nodes_elements.get(&socket.node)?;
+        element
+            .node
+            .needed_types_output()
+            .get(socket.socket)
+            .copied()
+    }
+
+    fn input_type(&self, socket: &NodeSocket) -> Option<TypeId> {
+        let element = self.nodes_elements.get(&socket.node)?;
+        element
+            .node
+            .needed_types_input()
+            .get(socket.socket)
+            .copied()
+    }
+
+    pub fn add_node<N, I, O>(&mut self, node: N) -> NodeId
     where
         I: InputOrOutput<T = I> + 'static,
-        O: InputOrOutput<T = O> + 'static,
+        O: Output<T = O> + 'static,
         N: Node<I, O> + 'static,
     {
-        self.nodes_elements.push(NodeGraphElement {
-            node: Box::new(DynNode::<N, I, O>::from(node)),
-            inputs: Vec::new(),
-            outputs: Vec::new(),
-        });
-        let index = self.nodes_elements.len() - 1;
+        // only group nodes can fail to be added
+        let id = NodeId(self.next_id);
+        self.insert_node(id, box_node(node));
+        id
+    }
 
-        if TypeId::of::<N>() == TypeId::of::<OutputNode>() {
-            self.output_node = Some(index);
+    // fails for an instance of a group that would end up containing itself
+    pub fn add_dyn_node(&mut self, node: Box<dyn NodeAny>) -> Result<NodeId, GraphError> {
+        if let (Some(group), Some(owner)) = (node.as_group(), self.owner.as_ref()) {
+            if group.contains(owner) {
+                return Err(GraphError::RecursiveGroup {
+                    name: group.name().to_string(),
+                });
+            }
         }
+        let id = NodeId(self.next_id);
+        self.insert_node(id, node);
+        Ok(id)
+    }
 
-        index
+    // the id the next node added gets
+    pub(crate) fn next_id(&self) -> NodeId {
+        NodeId(self.next_id)
     }
 
-    pub fn connect(&mut self, from: NodeSocket, to: NodeSocket) {
-        self.nodes_elements[to.node].inputs.push(Connection {
-            from: from.clone(),
-            to: to.clone(),
-        });
-        self.nodes_elements[from.node]
+    // makes sure no node added later gets an id below `id`, like the ids of removed nodes
+    pub(crate) fn reserve_ids(&mut self, id: NodeId) {
+        self.next_id = self.next_id.max(id.0);
+    }
+
+    pub(crate) fn set_owner(&mut self, owner: WeakGroup) {
+        self.owner = Some(owner);
+    }
+
+    // adds a node under a given id, used to keep the ids of a loaded graph
+    pub(crate) fn insert_node(&mut self, id: NodeId, node: Box<dyn NodeAny>) {
+        self.next_id = self.next_id.max(id.0 + 1);
+
+        self.nodes_elements.insert(
+            id,
+            NodeGraphElement {
+                node,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                cache: None,
+                defaults: BTreeMap::new(),
+                external_inputs: BTreeMap::new(),
+                version: 0,
+                error: None,
+                diagnostics: Vec::new(),
+            },
+        );
+    }
+
+    pub fn connect(&mut self, from: NodeSocket, to: NodeSocket) -> Result<(), GraphError> {
+        self.check_connection(&from, &to)?;
+        if self.depends_on(from.node, to.node) {
+            return Err(GraphError::Cycle { from, to });
+        }
+
+        if self.is_variadic(&to) {
+            // a variadic socket takes every connection, but only once
+            if self
+                .get_input_connections(&to)
+                .iter()
+                .any(|old| old.from == from)
+            {
+                return Ok(());
+            }
+        } else if let Some(old) = self.get_input_connection(&to) {
+            // an input socket only takes a single value, so the new connection replaces the old one
+            self.disconnect(&old.from, &old.to);
+        }
+
+        self.mark_dirty(to.node);
+        self.element_mut(to.node)
+            .inputs
+            .push(Connection::new(from.clone(), to.clone()));
+        self.element_mut(from.node)
             .outputs
-            .push(Connection { from, to });
+            .push(Connection::new(from, to));
+        Ok(())
+    }
+
+    // removes the connection between two sockets, returning it if it existed
+    pub fn disconnect(&mut self, from: &NodeSocket, to: &NodeSocket) -> Option<Connection> {
+        let to_node = self.nodes_elements.get_mut(&to.node)?;
+        let index = to_node
+            .inputs
+            .iter()
+            .position(|input| &input.from == from && &input.to == to)?;
+        let connection = to_node.inputs.remove(index);
+
+        if let Some(from_node) = self.nodes_elements.get_mut(&from.node) {
+            from_node
+                .outputs
+                .retain(|output| &output.from != from || &output.to != to);
+        }
+
+        self.mark_dirty(to.node);
+        Some(connection)
+    }
+
+    // the connection feeding an input socket
+    pub fn get_input_connection(&self, to: &NodeSocket) -> Option<Connection> {
+        self.nodes_elements
+            .get(&to.node)?
+            .inputs
+            .iter()
+            .find(|input| &input.to == to)
+            .cloned()
+    }
+
+    // every connection feeding an input socket, in the order they were made
+    pub fn get_input_connections(&self, to: &NodeSocket) -> Vec<Connection> {
+        self.nodes_elements
+            .get(&to.node)
+            .map(|element| {
+                element
+                    .inputs
+                    .iter()
+                    .filter(|input| &input.to == to)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // whether an input socket takes any number of connections
+    pub fn is_variadic(&self, socket: &NodeSocket) -> bool {
+        self.nodes_elements
+            .get(&socket.node)
+            .and_then(|element| element.node.variadic_inputs().get(socket.socket).copied())
+            .unwrap_or(false)
     }
 
-    pub fn get_output(&self) -> crate::Model {
-        let output_node = self.output_node.expect("Output node not found");
-        let output = self.get_output_of_node(output_node);
-        let model = output[0]
-            .downcast_ref::<Model>()
-            .expect("Output is not a model");
-        model.clone()
+    // removes a node and all of its connections
+    pub fn remove_node(&mut self, node: NodeId) -> Result<Box<dyn NodeAny>, GraphError> {
+        let element = self
+            .nodes_elements
+            .get(&node)
+            .ok_or(GraphError::NodeNotFound { node })?;
+
+        let connections: Vec<Connection> = element
+            .inputs
+            .iter()
+            .chain(element.outputs.iter())
+            .cloned()
+            .collect();
+        for connection in connections {
+            self.disconnect(&connection.from, &connection.to);
+        }
+
+        if let Some(profile) = self.profile.as_mut() {
+            profile.nodes.remove(&node);
+        }
+
+        let element = self
+            .nodes_elements
+            .remove(&node)
+            .expect("the node is in the graph");
+        Ok(element.node)
+    }
+
+    // checks every connection of the graph, returning all the problems found
+    pub fn validate(&self) -> Vec<GraphError> {
+        let mut errors = Vec::new();
+        for element in self.nodes_elements.values() {
+            for connection in element.inputs.iter() {
+                if let Err(error) = self.check_connection(&connection.from, &connection.to) {
+                    errors.push(error);
+                } else if self.depends_on(connection.from.node, connection.to.node) {
+                    errors.push(GraphError::Cycle {
+                        from: connection.from(),
+                        to: connection.to(),
+                    });
+                }
+            }
+        }
+        for (name, nodes) in self.output_nodes_by_name() {
+            if nodes.len() > 1 {
+                errors.push(GraphError::DuplicateOutput { name });
+            }
+        }
+        errors
     }
 
-    pub fn get_output_of_node(&self, node: usize) -> Vec<Box<dyn Any>> {
-        // if the node has no inputs, then we can just call the operation
-        if self.nodes_elements[node]
+    // checks that both ends of a connection exist and that their types match or can be converted
+    fn check_connection(&self, from: &NodeSocket, to: &NodeSocket) -> Result<(), GraphError> {
+        let from_node = self
+            .nodes_elements
+            .get(&from.node)
+            .ok_or(GraphError::NodeNotFound { node: from.node })?;
+        let to_node = self
+            .nodes_elements
+            .get(&to.node)
+            .ok_or(GraphError::NodeNotFound { node: to.node })?;
+
+        let found = *from_node
             .node
-            .needed_types_input()
-            .is_empty()
-        {
-            let operation_result = self.nodes_elements[node].node.operation(Vec::new());
-            operation_result
+            .needed_types_output()
+            .get(from.socket)
+            .ok_or(GraphError::SocketOutOfRange {
+                node: from.node,
+                socket: from.socket,
+            })?;
+        let expected = *to_node.node.needed_types_input().get(to.socket).ok_or(
+            GraphError::SocketOutOfRange {
+                node: to.node,
+                socket: to.socket,
+            },
+        )?;
+
+        if !self.conversions.can_convert(found, expected) {
+            return Err(GraphError::TypeMismatch {
+                from: from.clone(),
+                to: to.clone(),
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    // whether the value of `node` is computed from the value of `other`
+    pub(crate) fn depends_on(&self, node: NodeId, other: NodeId) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if node == other {
+                return true;
+            }
+            if !visited.insert(node) {
+                continue;
+            }
+            stack.extend(
+                self.element(node)
+                    .inputs
+                    .iter()
+                    .map(|input| input.from.node),
+            );
+        }
+        false
+    }
+
+    // forgets the cached outputs of a node and of every node downstream of it
+    pub fn mark_dirty(&mut self, node: NodeId) {
+        let mut visited = HashSet::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let Some(element) = self.nodes_elements.get_mut(&node) else {
+                continue;
+            };
+            element.cache = None;
+            element.error = None;
+            element.diagnostics.clear();
+            stack.extend(element.outputs.iter().map(|output| output.to.node));
+        }
+    }
+
+    // the output nodes by name, a name used by more than one node refers to the first of them
+    // `validate` reports such names, and looking the outputs up by name fails for them
+    pub fn get_output_nodes(&self) -> BTreeMap<String, NodeId> {
+        self.output_nodes_by_name()
+            .into_iter()
+            .map(|(name, nodes)| (name, nodes[0]))
+            .collect()
+    }
+
+    // every output node with each name
+    fn output_nodes_by_name(&self) -> BTreeMap<String, Vec<NodeId>> {
+        let mut outputs: BTreeMap<String, Vec<NodeId>> = BTreeMap::new();
+        for (id, element) in self.nodes_elements.iter() {
+            if !element.node.is_output() {
+                continue;
+            }
+            if let Some(ParameterValue::String(name)) = element.node.parameter("name") {
+                outputs.entry(name).or_default().push(*id);
+            }
+        }
+        outputs
+    }
+
+    // the output nodes by name, failing if a name is used by more than one of them
+    fn unique_output_nodes(&self) -> Result<BTreeMap<String, NodeId>, GraphError> {
+        self.output_nodes_by_name()
+            .into_iter()
+            .map(|(name, nodes)| match nodes[..] {
+                [node] => Ok((name, node)),
+                _ => Err(GraphError::DuplicateOutput { name }),
+            })
+            .collect()
+    }
+
+    // the model of the first output node, for graphs with a single output
+    pub fn get_output(&mut self) -> Result<Model, GraphError> {
+        let output_node = self
+            .nodes_elements
+            .iter()
+            .find(|(_, element)| element.node.is_output())
+            .map(|(id, _)| *id)
+            .ok_or(GraphError::NoOutputNode)?;
+        self.get_output_of_node(output_node)?;
+        self.output_value(output_node)
+    }
+
+    pub fn get_named_output(&mut self, name: &str) -> Result<Model, GraphError> {
+        self.get_output_as(name)
+    }
+
+    // the value of an output of any type, like a point cloud or a scalar
+    pub fn get_output_as<T: Clone + 'static>(&mut self, name: &str) -> Result<T, GraphError> {
+        let outputs = self.output_nodes_by_name();
+        let output_node = match outputs.get(name).map(Vec::as_slice) {
+            None => {
+                return Err(GraphError::OutputNotFound {
+                    name: name.to_string(),
+                })
+            }
+            Some([node]) => *node,
+            Some(_) => {
+                return Err(GraphError::DuplicateOutput {
+                    name: name.to_string(),
+                })
+            }
+        };
+        self.get_output_of_node(output_node)?;
+        self.output_value(output_node)
+    }
+
+    // the value of every output by name, evaluated together so the nodes they share run once
+    pub fn get_outputs(&mut self) -> Result<BTreeMap<String, Value>, GraphError> {
+        let outputs = self.unique_output_nodes()?;
+        if outputs.is_empty() {
+            return Err(GraphError::NoOutputNode);
+        }
+        self.evaluate(&outputs.values().copied().collect::<Vec<_>>())?;
+
+        Ok(outputs
+            .into_iter()
+            .filter_map(|(name, node)| {
+                let value = self.element(node).cache.as_ref()?.first()?.clone();
+                Some((name, value))
+            })
+            .collect())
+    }
+
+    // the value of an output node that was just evaluated
+    fn output_value<T: Clone + 'static>(&self, output_node: NodeId) -> Result<T, GraphError> {
+        let value = self
+            .element(output_node)
+            .cache
+            .as_ref()
+            .and_then(|output| output.first())
+            .ok_or(GraphError::DowncastFailed {
+                node: output_node,
+                socket: 0,
+            })?;
+        value
+            .downcast_ref::<T>()
+            .cloned()
+            .ok_or_else(|| GraphError::OutputTypeMismatch {
+                node: output_node,
+                expected: short_type_name(std::any::type_name::<T>()),
+            })
+    }
+
+    pub fn get_output_of_node(&mut self, node: NodeId) -> Result<Vec<Value>, GraphError> {
+        if !self.nodes_elements.contains_key(&node) {
+            return Err(GraphError::NodeNotFound { node });
+        }
+        self.evaluate(&[node])?;
+
+        Ok(self.element(node).cache.clone().unwrap_or_default())
+    }
+
+    // evaluates the nodes along with every node they depend on
+    fn evaluate(&mut self, nodes: &[NodeId]) -> Result<(), GraphError> {
+        // nodes that changed on their own, like groups whose definition was edited
+        let outdated: Vec<NodeId> = self
+            .nodes_elements
+            .iter()
+            .filter(|(_, element)| element.node.version() != element.version)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in outdated {
+            self.mark_dirty(id);
+            let element = self.element_mut(id);
+            element.version = element.node.version();
+        }
+
+        // only the nodes that changed since the last evaluation are run again
+        let (order, cached) = self.evaluation_order(nodes);
+        if let Some(profile) = self.profile.as_mut() {
+            for id in cached {
+                profile.record_cache_hit(id, self.nodes_elements[&id].node.name());
+            }
+        }
+
+        let mut evaluation = Evaluation::default();
+        if self.parallel {
+            self.evaluate_parallel(order, &mut evaluation);
         } else {
-            // if the node has inputs, then we need to get the output of the inputs
-            let mut inputs = Vec::new();
-            for input in self.nodes_elements[node].inputs.iter() {
-                // check the type validity of the input
-                let needed_type =
-                    self.nodes_elements[input.to.node].node.needed_types_input()[input.to.socket];
-
-                let actual_type = self.nodes_elements[input.from.node]
-                    .node
-                    .needed_types_output()[input.from.socket];
+            for (position, current) in order.into_iter().enumerate() {
+                if !self.skip_failed(current, &mut evaluation) {
+                    let run = self.run_node_recorded(current);
+                    self.store_result(position, current, run, &mut evaluation);
+                }
+            }
+        }
+        match evaluation.first_error {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    // the messages the nodes emitted the last time they ran, in the order of the nodes
+    pub fn get_diagnostics(&self) -> Vec<Diagnostic> {
+        self.nodes_elements
+            .values()
+            .flat_map(|element| element.diagnostics.iter().cloned())
+            .collect()
+    }
+
+    pub fn get_node_diagnostics(&self, node: NodeId) -> &[Diagnostic] {
+        self.nodes_elements
+            .get(&node)
+            .map(|element| element.diagnostics.as_slice())
+            .unwrap_or_default()
+    }
+
+    // the error of a node from its last evaluation, nodes after a failed node have none
+    pub fn get_node_error(&self, node: NodeId) -> Option<&GraphError> {
+        self.nodes_elements.get(&node)?.error.as_ref()
+    }
+
+    // evaluates the nodes in waves, a wave being every node whose inputs are already evaluated
+    // the nodes of a wave run at the same time, the nodes keep their position in the order given
+    // so the same error is returned no matter which node finishes first
+    fn evaluate_parallel(&mut self, order: Vec<NodeId>, evaluation: &mut Evaluation) {
+        let mut remaining: Vec<(usize, NodeId)> = order.into_iter().enumerate().collect();
+        while !remaining.is_empty() {
+            let pending: HashSet<NodeId> = remaining.iter().map(|&(_, id)| id).collect();
+            let (ready, waiting): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|&(_, id)| {
+                self.element(id)
+                    .inputs
+                    .iter()
+                    .all(|input| !pending.contains(&input.from.node))
+            });
+            let ready: Vec<(usize, NodeId)> = ready
+                .into_iter()
+                .filter(|&(_, id)| !self.skip_failed(id, evaluation))
+                .collect();
+
+            let graph = &*self;
+            let runs: Vec<NodeRun> = ready
+                .par_iter()
+                .map(|&(_, id)| graph.run_node_recorded(id))
+                .collect();
+
+            for ((position, id), run) in ready.into_iter().zip(runs) {
+                self.store_result(position, id, run, evaluation);
+            }
+
+            remaining = waiting;
+        }
+    }
+
+    // a node taking an input from a failed node is skipped, and counts as failed itself
+    fn skip_failed(&self, node: NodeId, evaluation: &mut Evaluation) -> bool {
+        let skipped = self
+            .element(node)
+            .inputs
+            .iter()
+            .any(|input| evaluation.failed.contains(&input.from.node));
+        if skipped {
+            evaluation.failed.insert(node);
+        }
+        skipped
+    }
+
+    fn store_result(
+        &mut self,
+        position: usize,
+        node: NodeId,
+        run: NodeRun,
+        evaluation: &mut Evaluation,
+    ) {
+        if let (Some(profile), Ok(output)) = (self.profile.as_mut(), &run.result) {
+            let name = self.nodes_elements[&node].node.name();
+            profile.record_run(node, name, run.time, output);
+        }
 
-                if needed_type != actual_type {
-                    panic!(
-                        "Invalid type for input, expected {:?}, got {:?}. From node: {}, to node: {}",
-                        needed_type, actual_type, input.from.node, input.to.node
-                    );
+        let element = self.element_mut(node);
+        element.diagnostics = run.diagnostics;
+        match run.result {
+            Ok(output) => {
+                element.cache = Some(output);
+                element.error = None;
+            }
+            Err(error) => {
+                element.error = Some(error.clone());
+                evaluation.failed.insert(node);
+                if evaluation
+                    .first_error
+                    .as_ref()
+                    .is_none_or(|(first, _)| position < *first)
+                {
+                    evaluation.first_error = Some((position, error));
                 }
+            }
+        }
+    }
+
+    // the dirty nodes the nodes depend on, ordered so that every node comes after its inputs
+    // along with the nodes whose cached outputs are used instead of running them
+    fn evaluation_order(&self, nodes: &[NodeId]) -> (Vec<NodeId>, Vec<NodeId>) {
+        let mut order = Vec::new();
+        let mut cached = Vec::new();
+        let mut visited = HashSet::new();
+        for &node in nodes {
+            self.visit_inputs(node, &mut visited, &mut order, &mut cached);
+        }
+        (order, cached)
+    }
+
+    fn visit_inputs(
+        &self,
+        node: NodeId,
+        visited: &mut HashSet<NodeId>,
+        order: &mut Vec<NodeId>,
+        cached: &mut Vec<NodeId>,
+    ) {
+        if !visited.insert(node) {
+            return;
+        }
+        if self.element(node).cache.is_some() {
+            cached.push(node);
+            return;
+        }
+
+        for input in self.element(node).inputs.iter() {
+            self.visit_inputs(input.from.node, visited, order, cached);
+        }
 
-                inputs.push((
-                    self.get_output_of_node(input.from.node)
-                        .remove(input.from.socket),
-                    input.to.socket,
-                ));
+        order.push(node);
+    }
+
+    // evaluates a node, measuring how long it took and collecting the messages it emitted
+    fn run_node_recorded(&self, node: NodeId) -> NodeRun {
+        let start = Instant::now();
+        let (result, diagnostics) = diagnostics::collect(node, || self.evaluate_node(node));
+        NodeRun {
+            result,
+            time: start.elapsed(),
+            diagnostics,
+        }
+    }
+
+    // runs a single node, taking its inputs from the cached outputs of the nodes before it
+    fn evaluate_node(&self, node: NodeId) -> Result<Vec<Value>, GraphError> {
+        let element = self.element(node);
+        let needed_types = element.node.needed_types_input();
+        let variadic = element.node.variadic_inputs();
+
+        // get the output of every input, placed at the socket it is connected to
+        let mut inputs: Vec<Option<Value>> = vec![None; needed_types.len()];
+        // the values going into variadic sockets, in the order they were connected
+        let mut lists: Vec<Vec<Value>> = vec![Vec::new(); needed_types.len()];
+        for input in element.inputs.iter() {
+            // check the type validity of the input
+            // a group can lose sockets when its definition is edited, leaving connections behind
+            let socket = input.to.socket;
+            let needed_type = *needed_types
+                .get(socket)
+                .ok_or(GraphError::SocketOutOfRange { node, socket })?;
+            let actual_type = *self
+                .element(input.from.node)
+                .node
+                .needed_types_output()
+                .get(input.from.socket)
+                .ok_or(GraphError::SocketOutOfRange {
+                    node: input.from.node,
+                    socket: input.from.socket,
+                })?;
+
+            if !self.conversions.can_convert(actual_type, needed_type) {
+                return Err(GraphError::TypeMismatch {
+                    from: input.from(),
+                    to: input.to(),
+                    expected: needed_type,
+                    found: actual_type,
+                });
             }
-            // sort the inputs by the socket index
-            inputs.sort_by(|a, b| a.1.cmp(&b.1));
 
-            if inputs.len() != self.nodes_elements[node].node.needed_types_input().len() {
-                panic!("Not enough inputs for node {}", node);
+            let Some(value) = self
+                .element(input.from.node)
+                .cache
+                .as_ref()
+                .and_then(|output| output.get(input.from.socket))
+            else {
+                continue;
+            };
+
+            let value = if needed_type == actual_type {
+                value.clone()
+            } else {
+                let converted = self
+                    .conversions
+                    .convert(value.as_ref(), actual_type, needed_type)
+                    .ok_or(GraphError::DowncastFailed { node, socket })?;
+                Value::from(converted)
+            };
+
+            if variadic[socket] {
+                lists[socket].push(value);
+            } else {
+                inputs[socket] = Some(value);
+            }
+        }
+
+        // values given from outside of the graph, like the inputs of a group
+        for (&socket, value) in element.external_inputs.iter() {
+            if socket >= needed_types.len() {
+                continue;
+            }
+            if variadic[socket] {
+                if let Some(list) = value.downcast_ref::<Vec<Value>>() {
+                    lists[socket].extend(list.iter().cloned());
+                }
+            } else if inputs[socket].is_none() {
+                inputs[socket] = Some(value.clone());
             }
+        }
+
+        // every socket needs a value, a variadic socket gets the list of its values
+        let mut values = Vec::new();
+        for (socket, (input, list)) in inputs.into_iter().zip(lists).enumerate() {
+            let value = match input {
+                _ if variadic[socket] => Arc::new(list) as Value,
+                Some(input) => input,
+                // sockets without a connection take their default value
+                None => self
+                    .get_input_default(&NodeSocket::new(node, socket))
+                    .map(|value| Value::from(value.to_any()))
+                    .ok_or(GraphError::MissingInput { node, socket })?,
+            };
+            values.push(value);
+        }
 
-            // get the references of the inputs as well as discarding the socket index
-            let input_refs: Vec<&dyn Any> = inputs.iter().map(|x| x.0.as_ref()).collect();
-            self.nodes_elements[node].node.operation(input_refs)
+        self.run_node(node, values)
+    }
+
+    // runs the operation of a node, turning a panic inside of it into an error
+    fn run_node(&self, node: NodeId, inputs: Vec<Value>) -> Result<Vec<Value>, GraphError> {
+        let node_any = self.element(node).node.as_ref();
+        match panic::catch_unwind(AssertUnwindSafe(|| node_any.operation(inputs))) {
+            Ok(result) => result.map_err(|error| GraphError::from_convert(node, error)),
+            Err(payload) => {
+                let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                    message.to_string()
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    message.clone()
+                } else {
+                    "the node panicked".to_string()
+                };
+                Err(GraphError::NodeFailed { node, message })
+            }
         }
     }
 
     pub fn get_nodes(&self) -> Vec<&dyn NodeAny> {
         self.nodes_elements
-            .iter()
+            .values()
             .map(|x| x.node.as_ref())
             .collect()
     }
 
-    pub fn get_node(&self, index: usize) -> &dyn NodeAny {
-        self.nodes_elements[index].node.as_ref()
+    // the ids of every node, in the order they were added
+    pub fn get_node_ids(&self) -> Vec<NodeId> {
+        self.nodes_elements.keys().copied().collect()
+    }
+
+    pub fn get_node(&self, id: NodeId) -> Option<&dyn NodeAny> {
+        self.nodes_elements
+            .get(&id)
+            .map(|element| element.node.as_ref())
+    }
+
+    // the parameters of a node, empty if there is no such node
+    pub fn get_parameters(&self, id: NodeId) -> Vec<(String, ParameterValue)> {
+        self.get_node(id)
+            .map(|node| node.parameters())
+            .unwrap_or_default()
+    }
+
+    pub fn get_parameter(&self, id: NodeId, name: &str) -> Option<ParameterValue> {
+        self.get_node(id)?.parameter(name)
+    }
+
+    // sets a parameter of a node, everything depending on it gets evaluated again
+    pub fn set_parameter(&mut self, id: NodeId, name: &str, value: ParameterValue) -> bool {
+        let Some(element) = self.nodes_elements.get_mut(&id) else {
+            return false;
+        };
+        if !element.node.set_parameter(name, value) {
+            return false;
+        }
+        self.mark_dirty(id);
+        true
+    }
+
+    // the value an input socket takes when nothing is connected to it
+    pub fn get_input_default(&self, socket: &NodeSocket) -> Option<ParameterValue> {
+        let element = self.nodes_elements.get(&socket.node)?;
+        match element.defaults.get(&socket.socket) {
+            Some(value) => Some(value.clone()),
+            None => {
+                element
+                    .node
+                    .input_sockets()
+                    .into_iter()
+                    .nth(socket.socket)?
+                    .default
+            }
+        }
+    }
+
+    // the values set for the input sockets of a node, without the defaults of the node itself
+    pub fn get_input_defaults(&self, node: NodeId) -> BTreeMap<usize, ParameterValue> {
+        self.nodes_elements
+            .get(&node)
+            .map(|element| element.defaults.clone())
+            .unwrap_or_default()
+    }
+
+    // sets the value an input socket takes when nothing is connected to it
+    pub fn set_input_default(
+        &mut self,
+        socket: &NodeSocket,
+        value: ParameterValue,
+    ) -> Result<(), GraphError> {
+        let element = self
+            .nodes_elements
+            .get_mut(&socket.node)
+            .ok_or(GraphError::NodeNotFound { node: socket.node })?;
+        let expected = *element.node.needed_types_input().get(socket.socket).ok_or(
+            GraphError::SocketOutOfRange {
+                node: socket.node,
+                socket: socket.socket,
+            },
+        )?;
+
+        // variadic sockets are an empty list when nothing is connected
+        let variadic = element.node.variadic_inputs()[socket.socket];
+        if variadic || value.value_type_id() != expected {
+            return Err(GraphError::InvalidDefault {
+                node: socket.node,
+                socket: socket.socket,
+            });
+        }
+
+        element.defaults.insert(socket.socket, value);
+        self.mark_dirty(socket.node);
+        Ok(())
+    }
+
+    // gives an unconnected input socket a value from outside of the graph, like the input of a group
+    pub(crate) fn set_external_input(&mut self, socket: &NodeSocket, value: Value) {
+        if let Some(element) = self.nodes_elements.get_mut(&socket.node) {
+            element.external_inputs.insert(socket.socket, value);
+            self.mark_dirty(socket.node);
+        }
+    }
+
+    // gives access to the node to edit it, everything depending on it gets evaluated again
+    pub fn get_node_mut<N: 'static>(&mut self, id: NodeId) -> Option<&mut N> {
+        self.mark_dirty(id);
+        self.nodes_elements
+            .get_mut(&id)?
+            .node
+            .inner_mut()
+            .downcast_mut::<N>()
     }
 
     pub fn get_connections(&self) -> Vec<Connection> {
-        let mut connections = Vec::new();
-        for node in self.nodes_elements.iter() {
-            connections.extend(node.inputs.iter().cloned());
-            connections.extend(node.outputs.iter().cloned());
+        // every connection is stored as an input of one node and an output of another
+        self.nodes_elements
+            .values()
+            .flat_map(|node| node.inputs.iter().cloned())
+            .collect()
+    }
+
+    // describes an error using the names of the nodes and sockets it is about
+    pub fn describe_error(&self, error: &GraphError) -> String {
+        let node_name = |node: NodeId| match self.get_node(node) {
+            Some(node_any) => format!("{} {}", node_any.name(), node),
+            None => node.to_string(),
+        };
+        let input = |socket: &NodeSocket| {
+            self.get_node(socket.node)
+                .and_then(|node| node.input_sockets().into_iter().nth(socket.socket))
+        };
+        let output = |socket: &NodeSocket| {
+            self.get_node(socket.node)
+                .and_then(|node| node.output_sockets().into_iter().nth(socket.socket))
+        };
+        let input_name = |node: NodeId, socket: usize| {
+            input(&NodeSocket::new(node, socket))
+                .map(|socket| socket.name)
+                .unwrap_or_else(|| format!("socket {}", socket))
+        };
+
+        match error {
+            GraphError::TypeMismatch { from, to, .. } => match (output(from), input(to)) {
+                (Some(output), Some(input)) => format!(
+                    "{} of {} is a {}, but {} of {} needs a {}",
+                    output.name,
+                    node_name(from.node),
+                    output.type_name,
+                    input.name,
+                    node_name(to.node),
+                    input.type_name
+                ),
+                _ => error.to_string(),
+            },
+            GraphError::MissingInput { node, socket } => format!(
+                "nothing is connected to {} of {}",
+                input_name(*node, *socket),
+                node_name(*node)
+            ),
+            GraphError::DowncastFailed { node, socket } => format!(
+                "{} of {} got a value of the wrong type",
+                input_name(*node, *socket),
+                node_name(*node)
+            ),
+            GraphError::NodeFailed { node, message } => {
+                format!("{} failed: {}", node_name(*node), message)
+            }
+            GraphError::OutputTypeMismatch { node, expected } => {
+                let found = output(&NodeSocket::new(*node, 0))
+                    .map(|socket| socket.type_name)
+                    .unwrap_or_default();
+                format!(
+                    "{} outputs a {}, not a {}",
+                    node_name(*node),
+                    found,
+                    expected
+                )
+            }
+            _ => error.to_string(),
         }
-        connections
+    }
+
+    // for nodes that are known to be in the graph, like the ends of an existing connection
+    fn element(&self, id: NodeId) -> &NodeGraphElement {
+        &self.nodes_elements[&id]
+    }
+
+    fn element_mut(&mut self, id: NodeId) -> &mut NodeGraphElement {
+        self.nodes_elements
+            .get_mut(&id)
+            .expect("the node is in the graph")
     }
 }
 
-pub trait NodeAny {
-    fn operation(&self, input: Vec<&dyn std::any::Any>) -> Vec<Box<dyn std::any::Any>>;
+pub trait NodeAny: Send + Sync {
+    fn operation(&self, input: Vec<Value>) -> Result<Vec<Value>, ConvertError>;
     fn needed_types_input(&self) -> Vec<std::any::TypeId>;
     fn needed_types_output(&self) -> Vec<std::any::TypeId>;
+    // which input sockets take any number of connections
+    fn variadic_inputs(&self) -> Vec<bool>;
     fn name(&self) -> &str;
     fn description(&self) -> &str;
+    fn input_sockets(&self) -> Vec<Socket>;
+    fn output_sockets(&self) -> Vec<Socket>;
+    fn identifier(&self) -> &str;
+    fn parameters(&self) -> Vec<(String, ParameterValue)>;
+    fn set_parameter(&mut self, name: &str, value: ParameterValue) -> bool;
+    fn parameter(&self, name: &str) -> Option<ParameterValue> {
+        self.parameters()
+            .into_iter()
+            .find(|(parameter, _)| parameter == name)
+            .map(|(_, value)| value)
+    }
+    // the type id of the node wrapped by this
+    fn node_type_id(&self) -> std::any::TypeId;
+    // whether the graph is evaluated for the outputs of this node
+    fn is_output(&self) -> bool {
+        false
+    }
+    // the node wrapped by this, to get it back as its concrete type
+    fn inner_mut(&mut self) -> &mut dyn std::any::Any;
+    // changes when the node changes without being edited through the graph
+    fn version(&self) -> u64 {
+        0
+    }
+    fn as_group(&self) -> Option<&GroupNode> {
+        None
+    }
+    // a copy of the node with the same parameters
+    fn clone_node(&self) -> Box<dyn NodeAny>;
+}
+
+impl Clone for Box<dyn NodeAny> {
+    fn clone(&self) -> Self {
+        self.clone_node()
+    }
+}
+
+// wraps a node so that it can be stored next to nodes of other types
+pub fn box_node<N, I, O>(node: N) -> Box<dyn NodeAny>
+where
+    I: InputOrOutput<T = I> + 'static,
+    O: Output<T = O> + 'static,
+    N: Node<I, O> + 'static,
+{
+    Box::new(DynNode::<N, I, O>::from(node))
 }
 
 struct DynNode<N, I, O>
 where
     N: Node<I, O>,
     I: InputOrOutput<T = I> + 'static,
-    O: InputOrOutput<T = O> + 'static,
+    O: Output<T = O> + 'static,
 {
     node: N,
-    _phantom: std::marker::PhantomData<(I, O)>,
+    // only used for its types, so it doesn't make the node any less Send or Sync
+    _phantom: std::marker::PhantomData<fn() -> (I, O)>,
     name: String,
     description: String,
+    identifier: String,
 }
 
 impl<N, I, O> NodeAny for DynNode<N, I, O>
 where
-    N: Node<I, O>,
+    N: Node<I, O> + 'static,
     I: InputOrOutput<T = I> + 'static,
-    O: InputOrOutput<T = O> + 'static,
+    O: Output<T = O> + 'static,
 {
-    fn operation(&self, input: Vec<&dyn std::any::Any>) -> Vec<Box<dyn std::any::Any>> {
-        let input = I::convert(input);
-        let output = self.node.operation(input);
-        O::convert_output(&output)
+    fn operation(&self, input: Vec<Value>) -> Result<Vec<Value>, ConvertError> {
+        let input = I::convert(
+            input
+                .iter()
+                .map(|value| value.as_ref() as &dyn Any)
+                .collect(),
+        )?;
+        let output = self.node.try_operation(input)?;
+        Ok(O::convert_output(output)
+            .into_iter()
+            .map(Value::from)
+            .collect())
     }
 
     fn needed_types_input(&self) -> Vec<std::any::TypeId> {
@@ -211,6 +1192,10 @@ where
         O::needed_types()
     }
 
+    fn variadic_inputs(&self) -> Vec<bool> {
+        I::variadic()
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -218,39 +1203,821 @@ where
     fn description(&self) -> &str {
         &self.description
     }
+
+    fn input_sockets(&self) -> Vec<Socket> {
+        input_sockets::<N, I, O>()
+    }
+
+    fn output_sockets(&self) -> Vec<Socket> {
+        output_sockets::<N, I, O>()
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        self.node.parameters()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: ParameterValue) -> bool {
+        self.node.set_parameter(name, value)
+    }
+
+    fn node_type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<N>()
+    }
+
+    fn is_output(&self) -> bool {
+        N::is_output()
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn std::any::Any {
+        &mut self.node
+    }
+
+    fn clone_node(&self) -> Box<dyn NodeAny> {
+        Box::new(Self {
+            node: self.node.clone(),
+            _phantom: std::marker::PhantomData,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            identifier: self.identifier.clone(),
+        })
+    }
 }
 
 impl<N, I, O> From<N> for DynNode<N, I, O>
 where
     N: Node<I, O>,
     I: InputOrOutput<T = I> + 'static,
-    O: InputOrOutput<T = O> + 'static,
+    O: Output<T = O> + 'static,
 {
     fn from(node: N) -> Self {
-        let mut name = std::any::type_name::<N>().to_string();
-        if N::name() != "" {
-            name = N::name().to_string();
-        }
-
         Self {
             node,
             _phantom: std::marker::PhantomData,
-            name,
+            name: node_name::<N, I, O>(),
             description: N::description(),
+            identifier: N::identifier(),
         }
     }
 }
 
-pub trait Node<I, O>
+// the name of a node, falling back to the name of its type
+pub(crate) fn node_name<N, I, O>() -> String
+where
+    N: Node<I, O>,
+    I: InputOrOutput<T = I> + 'static,
+    O: Output<T = O> + 'static,
+{
+    if N::name().is_empty() {
+        std::any::type_name::<N>().to_string()
+    } else {
+        N::name()
+    }
+}
+
+pub(crate) fn input_sockets<N, I, O>() -> Vec<Socket>
+where
+    N: Node<I, O>,
+    I: InputOrOutput<T = I> + 'static,
+    O: Output<T = O> + 'static,
+{
+    Socket::from_descriptors(
+        N::inputs(),
+        I::needed_types(),
+        I::type_names(),
+        I::variadic(),
+    )
+}
+
+pub(crate) fn output_sockets<N, I, O>() -> Vec<Socket>
+where
+    N: Node<I, O>,
+    I: InputOrOutput<T = I> + 'static,
+    O: Output<T = O> + 'static,
+{
+    Socket::from_descriptors(
+        N::outputs(),
+        O::needed_types(),
+        O::type_names(),
+        O::variadic(),
+    )
+}
+
+pub trait Node<I, O>: Clone + Send + Sync
 where
     I: InputOrOutput<T = I> + 'static + Sized,
-    O: InputOrOutput<T = O> + 'static + Sized,
+    O: Output<T = O> + 'static + Sized,
 {
-    fn operation(&self, input: I) -> O;
+    // the graph calls this, an error is reported for the node and skips the nodes after it
+    fn try_operation(&self, input: I) -> Result<O, NodeError>;
+    // runs the node outside of a graph, panicking if it fails
+    fn operation(&self, input: I) -> O {
+        match self.try_operation(input) {
+            Ok(output) => output,
+            Err(error) => panic!("{} failed: {}", Self::name(), error),
+        }
+    }
     fn name() -> String {
         "".to_string()
     }
     fn description() -> String {
         "".to_string()
     }
+    // the names of the input sockets in order, sockets without one are named after their type
+    fn inputs() -> Vec<SocketDescriptor> {
+        Vec::new()
+    }
+    // the names of the output sockets in order
+    fn outputs() -> Vec<SocketDescriptor> {
+        Vec::new()
+    }
+    // a name for the type of the node that doesn't change, used to save graphs
+    // there is no default, the name of the type changes when it is moved to another module
+    fn identifier() -> String;
+    // output nodes are the nodes a graph is evaluated for, found by name
+    fn is_output() -> bool {
+        false
+    }
+    // the values that make up the state of the node, saved along with the graph
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        Vec::new()
+    }
+    // returns false if the node has no parameter with this name and type
+    fn set_parameter(&mut self, _name: &str, _value: ParameterValue) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Level;
+    use crate::geometry::{Curve, PointCloud};
+    use crate::nodes::{
+        CircleNode, InstatiateOnPointsNode, MergeNode, OutputNode, ScaleInstanceNode, SphereNode,
+        ValueNode,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_missing_output_node() {
+        let mut node_graph = NodeGraph::new();
+        node_graph.add_node(SphereNode::default());
+        assert_eq!(
+            node_graph.get_output().err(),
+            Some(GraphError::NoOutputNode)
+        );
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let mut node_graph = NodeGraph::new();
+        let value = node_graph.add_node(ValueNode::new(0.5_f32));
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        node_graph
+            .connect(NodeSocket::new(value, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+
+        assert_eq!(
+            node_graph.get_output_of_node(scale).err(),
+            Some(GraphError::MissingInput {
+                node: scale,
+                socket: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let mut node_graph = NodeGraph::new();
+        let value = node_graph.add_node(ValueNode::new(true));
+        let merge = node_graph.add_node(MergeNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        assert!(matches!(
+            node_graph.connect(NodeSocket::new(value, 0), NodeSocket::new(merge, 0)),
+            Err(GraphError::TypeMismatch { to, .. }) if to == NodeSocket::new(merge, 0)
+        ));
+        assert!(node_graph.validate().is_empty());
+    }
+
+    #[test]
+    fn test_describe_error() {
+        let mut node_graph = NodeGraph::new();
+        let value = node_graph.add_node(ValueNode::new(true));
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+
+        assert_eq!(
+            node_graph.describe_error(&GraphError::MissingInput {
+                node: scale,
+                socket: 1
+            }),
+            "nothing is connected to Scale of Scale instance #1"
+        );
+
+        let error = node_graph
+            .connect(NodeSocket::new(value, 0), NodeSocket::new(scale, 0))
+            .unwrap_err();
+        assert_eq!(
+            node_graph.describe_error(&error),
+            "Value of Value #0 is a bool, but Instances of Scale instance #1 needs a Vec<Model>"
+        );
+    }
+
+    #[test]
+    fn test_implicit_conversions() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let amount = node_graph.add_node(ValueNode::new(2_i32));
+        let output = node_graph.add_node(OutputNode::default());
+
+        // the sphere is wrapped in a list, and the list is merged back into one model
+        let single_to_list = Connection::new(NodeSocket::new(sphere, 0), NodeSocket::new(scale, 0));
+        node_graph
+            .connect(single_to_list.from(), single_to_list.to())
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(amount, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        assert!(node_graph.is_converted(&single_to_list));
+        assert!(!node_graph.is_converted(&Connection::new(
+            NodeSocket::new(amount, 0),
+            NodeSocket::new(sphere, 0)
+        )));
+
+        let sphere_vertices = SphereNode::default().operation(()).0.vertices;
+        let output = node_graph.get_output().unwrap();
+        assert_eq!(output.vertices.len(), sphere_vertices.len());
+        assert_eq!(output.vertices[0], sphere_vertices[0] * 2.0);
+    }
+
+    #[test]
+    fn test_invalid_connections() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let output = node_graph.add_node(OutputNode::default());
+        let other_output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(output, 0), NodeSocket::new(other_output, 0))
+            .unwrap();
+
+        assert_eq!(
+            node_graph.connect(NodeSocket::new(sphere, 0), NodeSocket::new(NodeId(5), 0)),
+            Err(GraphError::NodeNotFound { node: NodeId(5) })
+        );
+        assert_eq!(
+            node_graph.connect(NodeSocket::new(sphere, 1), NodeSocket::new(output, 0)),
+            Err(GraphError::SocketOutOfRange {
+                node: sphere,
+                socket: 1
+            })
+        );
+        assert_eq!(
+            node_graph.connect(NodeSocket::new(other_output, 0), NodeSocket::new(output, 0)),
+            Err(GraphError::Cycle {
+                from: NodeSocket::new(other_output, 0),
+                to: NodeSocket::new(output, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_variadic_input() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let other_sphere = node_graph.add_node(SphereNode::default());
+        let merge = node_graph.add_node(MergeNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        // nothing connected is an empty list
+        assert_eq!(node_graph.get_output().unwrap().vertices.len(), 0);
+
+        let models = NodeSocket::new(merge, 0);
+        assert!(node_graph.is_variadic(&models));
+        for from in [sphere, other_sphere, sphere] {
+            node_graph
+                .connect(NodeSocket::new(from, 0), models.clone())
+                .unwrap();
+        }
+
+        // connecting the same socket again doesn't add it twice
+        let connections = node_graph.get_input_connections(&models);
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0].from(), NodeSocket::new(sphere, 0));
+        assert_eq!(connections[1].from(), NodeSocket::new(other_sphere, 0));
+
+        let sphere_vertices = SphereNode::default().operation(()).0.vertices.len();
+        assert_eq!(
+            node_graph.get_output().unwrap().vertices.len(),
+            sphere_vertices * 2
+        );
+
+        assert_eq!(
+            node_graph.set_input_default(&models, ParameterValue::Float(1.0)),
+            Err(GraphError::InvalidDefault {
+                node: merge,
+                socket: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_connect_replaces_input() {
+        let mut node_graph = NodeGraph::new();
+        let small = node_graph.add_node(ValueNode::new(0.5_f32));
+        let big = node_graph.add_node(ValueNode::new(2.0_f32));
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        node_graph
+            .connect(NodeSocket::new(small, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(big, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+
+        assert_eq!(node_graph.get_connections().len(), 1);
+        assert_eq!(
+            node_graph
+                .get_input_connection(&NodeSocket::new(scale, 1))
+                .unwrap()
+                .from(),
+            NodeSocket::new(big, 0)
+        );
+        assert!(node_graph.element(small).outputs.is_empty());
+
+        node_graph.disconnect(&NodeSocket::new(big, 0), &NodeSocket::new(scale, 1));
+        assert!(node_graph.get_connections().is_empty());
+        assert!(node_graph.element(big).outputs.is_empty());
+    }
+
+    #[test]
+    fn test_input_defaults() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let instatiate = node_graph.add_node(InstatiateOnPointsNode {});
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(instatiate, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(instatiate, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(instatiate, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+
+        let scale_socket = NodeSocket::new(scale, 1);
+        assert_eq!(
+            node_graph.get_input_default(&scale_socket),
+            Some(ParameterValue::Float(1.0))
+        );
+        node_graph.get_output_of_node(scale).unwrap();
+
+        assert_eq!(
+            node_graph.set_input_default(&scale_socket, ParameterValue::Int(2)),
+            Err(GraphError::InvalidDefault {
+                node: scale,
+                socket: 1
+            })
+        );
+        node_graph
+            .set_input_default(&scale_socket, ParameterValue::Float(2.0))
+            .unwrap();
+        let output = node_graph.get_output_of_node(scale).unwrap();
+        let models = output[0].downcast_ref::<Vec<Model>>().unwrap();
+        assert_eq!(models[0].transform()[0][0], 2.0);
+    }
+
+    #[test]
+    fn test_named_outputs() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut node_graph = NodeGraph::new();
+        let counting = node_graph.add_node(CountingNode { runs: runs.clone() });
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let render = node_graph.add_node(OutputNode::new("render"));
+        let collision = node_graph.add_node(OutputNode::new("collision"));
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(render, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+        node_graph
+            .set_input_default(&NodeSocket::new(scale, 1), ParameterValue::Float(2.0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(collision, 0))
+            .unwrap();
+
+        let outputs = node_graph.get_outputs().unwrap();
+        assert_eq!(
+            outputs.keys().collect::<Vec<_>>(),
+            vec!["collision", "render"]
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        // the scaled instance is merged back into a model, so the scale is in its vertices
+        let scaled = node_graph.get_named_output("collision").unwrap();
+        let unscaled = node_graph.get_named_output("render").unwrap();
+        assert_eq!(scaled.vertices[0], unscaled.vertices[0] * 2.0);
+        assert_eq!(
+            node_graph.get_named_output("lod1").err(),
+            Some(GraphError::OutputNotFound {
+                name: "lod1".to_string()
+            })
+        );
+
+        // outputs are renamed through their parameter
+        assert!(node_graph.set_parameter(
+            render,
+            "name",
+            ParameterValue::String("lod1".to_string())
+        ));
+        assert!(node_graph.get_named_output("lod1").is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_output_names() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        for _ in 0..2 {
+            let output = node_graph.add_node(OutputNode::new("render"));
+            node_graph
+                .connect(NodeSocket::new(sphere, 0), NodeSocket::new(output, 0))
+                .unwrap();
+        }
+
+        let duplicate = GraphError::DuplicateOutput {
+            name: "render".to_string(),
+        };
+        assert_eq!(node_graph.validate(), vec![duplicate.clone()]);
+        assert_eq!(
+            node_graph.get_named_output("render").err(),
+            Some(duplicate.clone())
+        );
+        assert_eq!(node_graph.get_outputs().err(), Some(duplicate));
+    }
+
+    #[test]
+    fn test_typed_outputs() {
+        let mut node_graph = NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let points = node_graph.add_node(OutputNode::<PointCloud>::typed("points"));
+        let circle = node_graph.add_node(CircleNode {});
+        let path = node_graph.add_node(OutputNode::<Curve>::typed("path"));
+        node_graph
+            .connect(NodeSocket::new(sphere, 0), NodeSocket::new(points, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(circle, 0), NodeSocket::new(path, 0))
+            .unwrap();
+
+        let sphere_vertices = SphereNode::default().operation(()).0.vertices.len();
+        let cloud = node_graph.get_output_as::<PointCloud>("points").unwrap();
+        assert_eq!(cloud.len(), sphere_vertices);
+        let curve = node_graph.get_output_as::<Curve>("path").unwrap();
+        assert!(curve.closed);
+        assert!((curve.length() - std::f32::consts::TAU).abs() < 0.1);
+        // the cached output and the values taken from it share their points
+        let again = node_graph.get_output_as::<Curve>("path").unwrap();
+        assert!(Arc::ptr_eq(&curve.points, &again.points));
+
+        assert_eq!(
+            node_graph.get_output_as::<Model>("path").err(),
+            Some(GraphError::OutputTypeMismatch {
+                node: path,
+                expected: "Model".to_string()
+            })
+        );
+        assert_eq!(node_graph.get_outputs().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_profiling() {
+        let mut node_graph = crate::example().unwrap();
+        node_graph.get_output().unwrap();
+        assert!(node_graph.profile_report().is_none());
+
+        node_graph.set_profiling(true);
+        node_graph.mark_dirty(NodeId(0));
+        node_graph.get_output().unwrap();
+        node_graph.mark_dirty(NodeId(2));
+        node_graph.get_output().unwrap();
+
+        let report = node_graph.profile_report().unwrap();
+        let sphere = SphereNode::default().operation(()).0;
+        let sphere_stats = &report.nodes[&NodeId(0)];
+        assert_eq!(sphere_stats.calls, 1);
+        assert_eq!(sphere_stats.vertices, sphere.vertices.len());
+        assert_eq!(sphere_stats.triangles, sphere.indices.len() / 3);
+
+        // the scale node ran again, using the cached instances
+        assert_eq!(report.nodes[&NodeId(1)].cache_hits, 1);
+        assert_eq!(report.nodes[&NodeId(2)].calls, 2);
+        assert_eq!(report.nodes.len(), 5);
+
+        node_graph.reset_profile();
+        assert!(node_graph.profile_report().unwrap().nodes.is_empty());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        for parallel in [true, false] {
+            let mut node_graph = crate::example().unwrap();
+            node_graph.set_parallel(parallel);
+            node_graph.get_output().unwrap();
+
+            let vertices = SphereNode::default().operation(()).0.vertices.len();
+            assert_eq!(
+                node_graph.get_diagnostics(),
+                vec![Diagnostic {
+                    node: NodeId(1),
+                    level: Level::Info,
+                    message: format!("spawning {} instances", vertices),
+                }]
+            );
+            assert!(node_graph.get_node_diagnostics(NodeId(0)).is_empty());
+
+            // the messages are kept while the node is cached and replaced when it runs again
+            node_graph.mark_dirty(NodeId(2));
+            node_graph.get_output().unwrap();
+            assert_eq!(node_graph.get_node_diagnostics(NodeId(1)).len(), 1);
+            node_graph.mark_dirty(NodeId(1));
+            assert!(node_graph.get_diagnostics().is_empty());
+        }
+
+        // nothing is collected outside of an evaluation
+        diagnostics::warning("ignored");
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut node_graph = crate::example().unwrap();
+        // the sphere feeding the instatiate node
+        node_graph.remove_node(NodeId(0)).unwrap();
+
+        assert_eq!(node_graph.get_nodes().len(), 4);
+        assert_eq!(node_graph.get_output_nodes()["output"], NodeId(4));
+        assert!(node_graph.validate().is_empty());
+        assert_eq!(
+            node_graph.get_output().err(),
+            Some(GraphError::MissingInput {
+                node: NodeId(1),
+                socket: 0
+            })
+        );
+        assert_eq!(
+            node_graph.remove_node(NodeId(0)).err(),
+            Some(GraphError::NodeNotFound { node: NodeId(0) })
+        );
+
+        node_graph.remove_node(NodeId(4)).unwrap();
+        assert_eq!(
+            node_graph.get_output().err(),
+            Some(GraphError::NoOutputNode)
+        );
+    }
+
+    #[derive(Clone)]
+    struct CountingNode {
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl Node<(), (Model,)> for CountingNode {
+        fn try_operation(&self, _: ()) -> Result<(Model,), NodeError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            SphereNode::default().try_operation(())
+        }
+
+        fn identifier() -> String {
+            "counting".to_string()
+        }
+    }
+
+    // many independent branches merged together
+    fn wide_graph(parallel: bool) -> (NodeGraph, Vec<NodeId>) {
+        let mut node_graph = NodeGraph::new();
+        node_graph.set_parallel(parallel);
+        let merge = node_graph.add_node(MergeNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        let mut scales = Vec::new();
+        for i in 0..8 {
+            let sphere = node_graph.add_node(SphereNode::default());
+            let scale = node_graph.add_node(ScaleInstanceNode {});
+            node_graph
+                .connect(NodeSocket::new(sphere, 0), NodeSocket::new(scale, 0))
+                .unwrap();
+            node_graph
+                .set_input_default(
+                    &NodeSocket::new(scale, 1),
+                    ParameterValue::Float(i as f32 + 1.0),
+                )
+                .unwrap();
+            node_graph
+                .connect(NodeSocket::new(scale, 0), NodeSocket::new(merge, 0))
+                .unwrap();
+            scales.push(scale);
+        }
+        (node_graph, scales)
+    }
+
+    #[test]
+    fn test_parallel_matches_serial() {
+        let (mut parallel, scales) = wide_graph(true);
+        let (mut serial, _) = wide_graph(false);
+        assert_eq!(
+            parallel.get_output().unwrap().vertices,
+            serial.get_output().unwrap().vertices
+        );
+
+        // with two failing branches the same error is returned every time
+        for node_graph in [&mut parallel, &mut serial] {
+            for &scale in &scales[2..4] {
+                let input = node_graph.get_input_connection(&NodeSocket::new(scale, 0));
+                let input = input.unwrap();
+                node_graph.disconnect(&input.from(), &input.to());
+            }
+        }
+        let error = Some(GraphError::MissingInput {
+            node: scales[2],
+            socket: 0,
+        });
+        for _ in 0..10 {
+            parallel.mark_dirty(scales[0]);
+            assert_eq!(parallel.get_output().err(), error);
+        }
+        assert_eq!(serial.get_output().err(), error);
+    }
+
+    #[test]
+    fn test_parallel_error_order() {
+        // the scale node fails in the second wave but comes before the sphere in evaluation order
+        for parallel in [true, false] {
+            let mut node_graph = NodeGraph::new();
+            node_graph.set_parallel(parallel);
+            let value = node_graph.add_node(ValueNode::new(2.0_f32));
+            let scale = node_graph.add_node(ScaleInstanceNode {});
+            let sphere = node_graph.add_node(SphereNode::new(0));
+            let merge = node_graph.add_node(MergeNode {});
+            let output = node_graph.add_node(OutputNode::default());
+            node_graph
+                .connect(NodeSocket::new(value, 0), NodeSocket::new(scale, 1))
+                .unwrap();
+            node_graph
+                .connect(NodeSocket::new(scale, 0), NodeSocket::new(merge, 0))
+                .unwrap();
+            node_graph
+                .connect(NodeSocket::new(sphere, 0), NodeSocket::new(merge, 0))
+                .unwrap();
+            node_graph
+                .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+                .unwrap();
+
+            assert_eq!(
+                node_graph.get_output().err(),
+                Some(GraphError::MissingInput {
+                    node: scale,
+                    socket: 0
+                })
+            );
+        }
+    }
+
+    // makes a sphere, failing for a negative radius
+    #[derive(Clone)]
+    struct RadiusNode {}
+
+    impl Node<(f32,), (Model,)> for RadiusNode {
+        fn try_operation(&self, (radius,): (f32,)) -> Result<(Model,), NodeError> {
+            if radius < 0.0 {
+                return Err(NodeError::new("the radius is negative"));
+            }
+            Ok(SphereNode::default().operation(()))
+        }
+
+        fn identifier() -> String {
+            "radius".to_string()
+        }
+    }
+
+    #[test]
+    fn test_node_errors() {
+        for parallel in [true, false] {
+            let mut node_graph = NodeGraph::new();
+            node_graph.set_parallel(parallel);
+            let value = node_graph.add_node(ValueNode::new(-1.0_f32));
+            let radius = node_graph.add_node(RadiusNode {});
+            let output = node_graph.add_node(OutputNode::default());
+            node_graph
+                .connect(NodeSocket::new(value, 0), NodeSocket::new(radius, 0))
+                .unwrap();
+            node_graph
+                .connect(NodeSocket::new(radius, 0), NodeSocket::new(output, 0))
+                .unwrap();
+
+            let error = GraphError::NodeFailed {
+                node: radius,
+                message: "the radius is negative".to_string(),
+            };
+            assert_eq!(node_graph.get_output().err(), Some(error.clone()));
+            assert_eq!(node_graph.get_node_error(radius), Some(&error));
+            // the output node is skipped rather than failing on the missing input
+            assert_eq!(node_graph.get_node_error(output), None);
+
+            node_graph
+                .get_node_mut::<ValueNode<f32>>(value)
+                .unwrap()
+                .set_value(1.0);
+            assert!(node_graph.get_output().is_ok());
+            assert_eq!(node_graph.get_node_error(radius), None);
+        }
+    }
+
+    #[test]
+    fn test_shared_input_runs_once() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut node_graph = NodeGraph::new();
+        let counting = node_graph.add_node(CountingNode { runs: runs.clone() });
+        let instatiate = node_graph.add_node(InstatiateOnPointsNode {});
+        let merge = node_graph.add_node(MergeNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(instatiate, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(instatiate, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(instatiate, 0), NodeSocket::new(merge, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        node_graph.get_output().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_only_dirty_nodes_run_again() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut node_graph = NodeGraph::new();
+        let counting = node_graph.add_node(CountingNode { runs: runs.clone() });
+        let instatiate = node_graph.add_node(InstatiateOnPointsNode {});
+        let scale = node_graph.add_node(ScaleInstanceNode {});
+        let value = node_graph.add_node(ValueNode::new(1.0_f32));
+        let merge = node_graph.add_node(MergeNode {});
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(instatiate, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(counting, 0), NodeSocket::new(instatiate, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(instatiate, 0), NodeSocket::new(scale, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(value, 0), NodeSocket::new(scale, 1))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(scale, 0), NodeSocket::new(merge, 0))
+            .unwrap();
+        node_graph
+            .connect(NodeSocket::new(merge, 0), NodeSocket::new(output, 0))
+            .unwrap();
+
+        let first = node_graph.get_output().unwrap();
+        node_graph
+            .get_node_mut::<ValueNode<f32>>(value)
+            .unwrap()
+            .set_value(2.0);
+        let second = node_graph.get_output().unwrap();
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_ne!(first.vertices, second.vertices);
+    }
+
+    #[test]
+    fn test_example_output() {
+        let model = crate::example().unwrap().get_output().unwrap();
+        assert!(!model.vertices.is_empty());
+    }
 }
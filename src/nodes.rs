@@ -1,46 +1,134 @@
-use crate::Model;
-use crate::{macros::InputOrOutput, Node};
+use mesh_mancer_macros::node;
+
+use crate::diagnostics;
+use crate::geometry::{Curve, PointCloud};
+use crate::parameter::{ParameterType, ParameterValue};
+use crate::registry::NodeRegistry;
+use crate::socket::Variadic;
+use crate::{Model, NodeError};
+use std::marker::PhantomData;
 use three_d::{CpuMesh, Matrix4, Vector3};
 
+// a type that output nodes can give out of the graph
+pub trait OutputType: Clone + Send + Sync + 'static {
+    // the identifier of the output node for this type
+    const IDENTIFIER: &'static str;
+    const NAME: &'static str;
+}
+
+impl OutputType for Model {
+    const IDENTIFIER: &'static str = "output";
+    const NAME: &'static str = "Output";
+}
+
+impl OutputType for Vec<Model> {
+    const IDENTIFIER: &'static str = "output_instances";
+    const NAME: &'static str = "Instances output";
+}
+
+impl OutputType for PointCloud {
+    const IDENTIFIER: &'static str = "output_points";
+    const NAME: &'static str = "Points output";
+}
+
+impl OutputType for f32 {
+    const IDENTIFIER: &'static str = "output_scalar";
+    const NAME: &'static str = "Scalar output";
+}
+
+impl OutputType for Curve {
+    const IDENTIFIER: &'static str = "output_curve";
+    const NAME: &'static str = "Curve output";
+}
+
 // just acts as a tag to get the output of the graph
-pub struct OutputNode {}
+// a graph can have several outputs, they are told apart by their names
+#[derive(Clone)]
+pub struct OutputNode<T = Model> {
+    name: String,
+    _phantom: PhantomData<fn() -> T>,
+}
 
-impl Node<(Model,), (Model,)> for OutputNode {
-    fn operation(&self, model: (Model,)) -> (Model,) {
-        model
+impl OutputNode {
+    pub fn new(name: &str) -> Self {
+        Self::typed(name)
     }
+}
 
-    fn name() -> String {
-        "Output".to_string()
+impl<T> OutputNode<T> {
+    // an output of another type than a model
+    pub fn typed(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            _phantom: PhantomData,
+        }
     }
 
-    fn description() -> String {
-        "Outputs the model to be displayed".to_string()
+    pub fn output_name(&self) -> &str {
+        &self.name
     }
 }
 
-pub struct TransformNode {}
+impl Default for OutputNode {
+    fn default() -> Self {
+        Self::new(DEFAULT_OUTPUT)
+    }
+}
 
-impl Node<(Model, Vector3<f32>), (Model,)> for TransformNode {
-    fn operation(&self, info: (Model, Vector3<f32>)) -> (Model,) {
-        let mut model = info.0;
-        let vector3 = info.1;
+// the name of an output node unless it is given another one
+pub const DEFAULT_OUTPUT: &str = "output";
 
-        let transform = Matrix4::from_translation(vector3);
-        model.set_transform(transform);
+/// Outputs the value to be displayed or exported
+#[node(
+    name = T::NAME,
+    identifier = T::IDENTIFIER,
+    output("Value", "The output value")
+)]
+impl<T> OutputNode<T>
+where
+    T: OutputType,
+{
+    fn operation(
+        &self,
+        /// The value to output
+        value: T,
+    ) -> T {
+        value
+    }
 
-        (model,)
+    fn is_output() -> bool {
+        true
     }
 
-    fn name() -> String {
-        "Transform".to_string()
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        vec![("name".to_string(), self.name.to_parameter())]
     }
 
-    fn description() -> String {
-        "Transforms the model".to_string()
+    fn set_parameter(&mut self, name: &str, value: ParameterValue) -> bool {
+        match String::from_parameter(&value) {
+            Some(value) if name == "name" => {
+                self.name = value;
+                true
+            }
+            _ => false,
+        }
     }
 }
 
+/// Transforms the model
+#[node(output("Model", "The moved model"))]
+pub fn transform(
+    /// The model to move
+    mut model: Model,
+    /// How far to move the model
+    #[default(Vector3::new(0.0_f32, 0.0, 0.0))]
+    offset: Vector3<f32>,
+) -> Model {
+    model.set_transform(Matrix4::from_translation(offset));
+    model
+}
+
+#[derive(Clone)]
 pub struct ValueNode<T> {
     value: T,
 }
@@ -49,39 +137,91 @@ impl<T> ValueNode<T> {
     pub fn new(value: T) -> Self {
         Self { value }
     }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: T) {
+        self.value = value;
+    }
 }
 
-impl<T> Node<((),), (T,)> for ValueNode<T>
+/// Outputs a value
+#[node(
+    name = "Value",
+    identifier = format!("value_{}", T::NAME),
+    output("Value", "The value of the node")
+)]
+impl<T> ValueNode<T>
 where
-    T: Clone + 'static,
+    T: ParameterType,
 {
-    fn operation(&self, _: ((),)) -> (T,) {
-        (self.value.clone(),)
+    fn operation(&self) -> T {
+        self.value.clone()
+    }
+
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        vec![("value".to_string(), self.value.to_parameter())]
     }
 
-    fn name() -> String {
-        "Value".to_string()
+    fn set_parameter(&mut self, name: &str, value: ParameterValue) -> bool {
+        match T::from_parameter(&value) {
+            Some(value) if name == "value" => {
+                self.value = value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SphereNode {
+    // how many rings the sphere has from pole to pole
+    subdivisions: i32,
+}
+
+impl SphereNode {
+    pub fn new(subdivisions: i32) -> Self {
+        Self { subdivisions }
     }
+}
 
-    fn description() -> String {
-        "Outputs a value".to_string()
+impl Default for SphereNode {
+    fn default() -> Self {
+        Self::new(6)
     }
 }
 
-pub struct SphereNode {}
+/// Generates a sphere
+#[node(
+    name = "Sphere",
+    identifier = "sphere",
+    output("Sphere", "A sphere with a radius of 1")
+)]
+impl SphereNode {
+    fn operation(&self) -> Result<Model, NodeError> {
+        // more subdivisions have more vertices than the indices of the mesh can reach
+        if !(2..=100).contains(&self.subdivisions) {
+            return Err(NodeError::new(
+                "the sphere needs between 2 and 100 subdivisions",
+            ));
+        }
 
-impl Node<((),), (Model,)> for SphereNode {
-    fn operation(&self, _: ((),)) -> (Model,) {
         let mut model = crate::Model::new();
 
-        let sphere = CpuMesh::sphere(6);
+        let sphere = CpuMesh::sphere(self.subdivisions as u32);
         for vertex in sphere.positions.into_f32().iter() {
             model.add_vertex(vertex.x, vertex.y, vertex.z);
         }
         let mut a = None;
         let mut b = None;
         let mut c = None;
-        let indices = sphere.indices.into_u32().expect("Indices are not u32");
+        let indices = sphere
+            .indices
+            .into_u32()
+            .ok_or_else(|| NodeError::new("the sphere mesh has no indices"))?;
         for index in indices.iter() {
             if a.is_none() {
                 a = Some(*index);
@@ -95,96 +235,132 @@ impl Node<((),), (Model,)> for SphereNode {
                 c = None;
             }
         }
-        (model,)
-    }
-
-    fn name() -> String {
-        "Sphere".to_string()
+        Ok(model)
     }
 
-    fn description() -> String {
-        "Generates a sphere".to_string()
+    fn parameters(&self) -> Vec<(String, ParameterValue)> {
+        vec![("subdivisions".to_string(), self.subdivisions.to_parameter())]
     }
-}
-
-pub struct InstatiateOnPointsNode {}
-
-impl Node<(Model, Model), (Vec<Model>,)> for InstatiateOnPointsNode {
-    fn operation(&self, info: (Model, Model)) -> (Vec<Model>,) {
-        let model = info.0;
-        let points = info.1;
-
-        println!("spawning: {} instances", points.vertices.iter().count());
 
-        let mut models = Vec::new();
-        for vertex in points.vertices.iter() {
-            let mut m = model.clone();
-            m.set_transform(Matrix4::from_translation(*vertex));
-            models.push(m);
+    fn set_parameter(&mut self, name: &str, value: ParameterValue) -> bool {
+        match i32::from_parameter(&value) {
+            Some(subdivisions) if name == "subdivisions" => {
+                self.subdivisions = subdivisions;
+                true
+            }
+            _ => false,
         }
-
-        (models,)
     }
+}
 
-    fn name() -> String {
-        "Instatiate on points".to_string()
+/// Generates a circle around the vertical axis
+#[node(output("Circle", "A closed curve through the points of the circle"))]
+pub fn circle(
+    /// How far the points are from the center
+    #[default(1.0_f32)]
+    radius: f32,
+    /// How many points the circle has
+    #[default(32_i32)]
+    segments: i32,
+) -> Result<Curve, NodeError> {
+    if segments < 3 {
+        return Err(NodeError::new("a circle needs at least 3 segments"));
     }
 
-    fn description() -> String {
-        "Instatiates the model on the points of the other model".to_string()
-    }
+    let points = (0..segments)
+        .map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / segments as f32;
+            Vector3::new(angle.cos() * radius, 0.0, angle.sin() * radius)
+        })
+        .collect();
+    Ok(Curve::new(points, true))
 }
 
-pub struct ScaleInstanceNode {}
-
-impl Node<(Vec<Model>, f32), (Vec<Model>,)> for ScaleInstanceNode {
-    fn operation(&self, info: (Vec<Model>, f32)) -> (Vec<Model>,) {
-        let models = info.0;
-        let scale = info.1;
-
-        let mut new_models = Vec::new();
-        for mut model in models {
-            model.set_transform(model.transform() * Matrix4::from_scale(scale));
-            new_models.push(model);
-        }
-
-        (new_models,)
+/// Instatiates the model on the points of the other model
+#[node(
+    identifier = "instantiate_on_points",
+    output("Instances", "A copy of the model for every point")
+)]
+pub fn instatiate_on_points(
+    /// The model placed on every point
+    instance: Model,
+    /// The model whose vertices are the points
+    points: Model,
+) -> Vec<Model> {
+    if points.vertices.is_empty() {
+        diagnostics::warning("there are no points to place the instances on");
+    } else {
+        diagnostics::info(format!("spawning {} instances", points.vertices.len()));
     }
 
-    fn name() -> String {
-        "Scale instance".to_string()
+    let mut models = Vec::new();
+    for vertex in points.vertices.iter() {
+        let mut m = instance.clone();
+        m.set_transform(Matrix4::from_translation(*vertex));
+        models.push(m);
     }
 
-    fn description() -> String {
-        "Scales the instances".to_string()
-    }
+    models
 }
 
-pub struct MergeNode {}
+/// Scales the instances
+#[node(output("Instances", "The scaled models"))]
+pub fn scale_instance(
+    /// The models to scale
+    instances: Vec<Model>,
+    /// How much to scale every model
+    #[default(1.0_f32)]
+    scale: f32,
+) -> Vec<Model> {
+    let mut new_models = Vec::new();
+    for mut model in instances {
+        model.set_transform(model.transform() * Matrix4::from_scale(scale));
+        new_models.push(model);
+    }
 
-impl Node<(Vec<crate::Model>,), (crate::Model,)> for MergeNode {
-    fn operation(&self, info: (Vec<crate::Model>,)) -> (crate::Model,) {
-        let mut model = crate::Model::new();
+    new_models
+}
 
-        for m in info.0 {
-            model.merge(&m);
-        }
+/// Merges the models
+#[node(output("Model", "A single model with every part"))]
+pub fn merge(
+    /// The models to merge, any number of them can be connected
+    models: Variadic<Model>,
+) -> Model {
+    let mut model = crate::Model::new();
 
-        (model,)
+    for m in models {
+        model.merge(&m);
     }
 
-    fn name() -> String {
-        "Merge".to_string()
-    }
+    model
+}
 
-    fn description() -> String {
-        "Merges the models".to_string()
-    }
+// registers every node in this file
+pub fn register_nodes(registry: &mut NodeRegistry) {
+    registry.register(OutputNode::default);
+    registry.register(|| OutputNode::<Vec<Model>>::typed(DEFAULT_OUTPUT));
+    registry.register(|| OutputNode::<PointCloud>::typed(DEFAULT_OUTPUT));
+    registry.register(|| OutputNode::<f32>::typed(DEFAULT_OUTPUT));
+    registry.register(|| OutputNode::<Curve>::typed(DEFAULT_OUTPUT));
+    TransformNode::register(registry);
+    registry.register(|| ValueNode::new(0.0_f32));
+    registry.register(|| ValueNode::new(0_i32));
+    registry.register(|| ValueNode::new(Vector3::new(0.0_f32, 0.0, 0.0)));
+    registry.register(|| ValueNode::new(false));
+    registry.register(|| ValueNode::new(String::new()));
+    registry.register(SphereNode::default);
+    CircleNode::register(registry);
+    InstatiateOnPointsNode::register(registry);
+    ScaleInstanceNode::register(registry);
+    MergeNode::register(registry);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Node;
+    use std::sync::Arc;
     use three_d::Vector3;
 
     #[test]
@@ -198,4 +374,70 @@ mod tests {
         let model = transform_node.operation((model, vector3));
         assert_eq!(model.0.transform, Matrix4::from_translation(vector3));
     }
+
+    #[test]
+    fn test_instances_share_buffers() {
+        let (sphere,) = SphereNode::default().operation(());
+        let (moved,) = TransformNode {}.operation((sphere.clone(), Vector3::new(1.0, 0.0, 0.0)));
+        assert!(Arc::ptr_eq(&moved.vertices, &sphere.vertices));
+
+        let (instances,) = InstatiateOnPointsNode {}.operation((sphere.clone(), sphere.clone()));
+        let (scaled,) = ScaleInstanceNode {}.operation((instances, 0.5));
+        assert!(scaled
+            .iter()
+            .all(|model| Arc::ptr_eq(&model.vertices, &sphere.vertices)
+                && Arc::ptr_eq(&model.indices, &sphere.indices)));
+    }
+
+    #[test]
+    fn test_sphere_subdivisions() {
+        let mut node_graph = crate::NodeGraph::new();
+        let sphere = node_graph.add_node(SphereNode::default());
+        let output = node_graph.add_node(OutputNode::default());
+        node_graph
+            .connect(
+                crate::NodeSocket::new(sphere, 0),
+                crate::NodeSocket::new(output, 0),
+            )
+            .unwrap();
+        let vertices = node_graph.get_output().unwrap().vertices.len();
+
+        assert_eq!(
+            node_graph.get_parameter(sphere, "subdivisions"),
+            Some(ParameterValue::Int(6))
+        );
+        assert!(node_graph.set_parameter(sphere, "subdivisions", ParameterValue::Int(12)));
+        assert!(node_graph.get_output().unwrap().vertices.len() > vertices);
+
+        // out of range subdivisions fail instead of making a broken mesh
+        assert!(node_graph.set_parameter(sphere, "subdivisions", ParameterValue::Int(0)));
+        assert!(node_graph.get_output().is_err());
+        assert!(!node_graph.set_parameter(sphere, "subdivisions", ParameterValue::Float(1.0)));
+    }
+
+    /// Splits a number
+    #[node(output("Whole"), output("Fraction", "What is left"))]
+    fn split_number(
+        /// The number to split
+        #[default(1.5_f32)]
+        number: f32,
+    ) -> (i32, f32) {
+        (number.trunc() as i32, number.fract())
+    }
+
+    #[test]
+    fn test_node_macro() {
+        assert_eq!(SplitNumberNode::name(), "Split number");
+        assert_eq!(SplitNumberNode::identifier(), "split_number");
+        assert_eq!(SplitNumberNode::description(), "Splits a number");
+
+        let inputs = SplitNumberNode::inputs();
+        assert_eq!(inputs[0].name, "Number");
+        assert_eq!(inputs[0].description, "The number to split");
+        assert_eq!(inputs[0].default, Some(1.5_f32.to_parameter()));
+        let outputs = SplitNumberNode::outputs();
+        assert_eq!(outputs[1].name, "Fraction");
+
+        assert_eq!(SplitNumberNode {}.operation((2.25,)), (2, 0.25));
+    }
 }
@@ -0,0 +1,200 @@
+use std::str::FromStr;
+
+use crate::parameter::ParameterValue;
+use crate::{NodeGraph, NodeId, NodeSocket, OverrideError};
+
+// a value set on a graph from outside of its file, like `--set scale=0.2` on the command line
+// the name is the name of a parameter, or of an input socket without a connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Override {
+    // the node to set the value on, every node with the name when None
+    pub node: Option<NodeId>,
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for Override {
+    type Err = OverrideError;
+
+    // reads `name=value` or `node.name=value`, like `scale=0.2` or `0.subdivisions=12`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let syntax = || OverrideError::Syntax(text.to_string());
+        let (target, value) = text.split_once('=').ok_or_else(syntax)?;
+        let (node, name) = match target.split_once('.') {
+            Some((node, name)) => {
+                let index = node.trim_start_matches('#').parse().map_err(|_| syntax())?;
+                (Some(NodeId::from_raw(index)), name)
+            }
+            None => (None, target),
+        };
+        if name.is_empty() {
+            return Err(syntax());
+        }
+        Ok(Self {
+            node,
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+// a value an override sets, read before anything is set so a bad value leaves the graph as it was
+enum Target {
+    Parameter(NodeId, ParameterValue),
+    Input(NodeSocket, ParameterValue),
+}
+
+impl Override {
+    // sets the value on the nodes it targets, returning how many values were set
+    // parameters are matched by their exact name, input sockets ignoring the case
+    pub fn apply(&self, node_graph: &mut NodeGraph) -> Result<usize, OverrideError> {
+        let targets = self.targets(node_graph)?;
+        if targets.is_empty() {
+            return Err(OverrideError::NotFound {
+                name: self.name.clone(),
+            });
+        }
+
+        for target in targets.iter() {
+            let applied = match target {
+                Target::Parameter(id, value) => {
+                    node_graph.set_parameter(*id, &self.name, value.clone())
+                }
+                Target::Input(socket, value) => {
+                    node_graph.set_input_default(socket, value.clone()).is_ok()
+                }
+            };
+            if !applied {
+                return Err(self.invalid_value());
+            }
+        }
+        Ok(targets.len())
+    }
+
+    // the values to set, failing if any of the targets can't take the value
+    fn targets(&self, node_graph: &NodeGraph) -> Result<Vec<Target>, OverrideError> {
+        let mut targets = Vec::new();
+        for id in node_graph.get_node_ids() {
+            if self.node.is_some_and(|node| node != id) {
+                continue;
+            }
+
+            if let Some(current) = node_graph.get_parameter(id, &self.name) {
+                let value = current
+                    .parse_as(&self.value)
+                    .ok_or_else(|| self.invalid_value())?;
+                targets.push(Target::Parameter(id, value));
+                continue;
+            }
+
+            let Some(node) = node_graph.get_node(id) else {
+                continue;
+            };
+            for (index, socket) in node.input_sockets().iter().enumerate() {
+                let input = NodeSocket::new(id, index);
+                if !socket.name.eq_ignore_ascii_case(&self.name)
+                    || !node_graph.get_input_connections(&input).is_empty()
+                {
+                    continue;
+                }
+                // sockets without a default, like those taking a model, can't be set
+                let Some(current) = node_graph.get_input_default(&input) else {
+                    continue;
+                };
+                let value = current
+                    .parse_as(&self.value)
+                    .ok_or_else(|| self.invalid_value())?;
+                targets.push(Target::Input(input, value));
+            }
+        }
+        Ok(targets)
+    }
+
+    fn invalid_value(&self) -> OverrideError {
+        OverrideError::InvalidValue {
+            name: self.name.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::ValueNode;
+    use crate::{example, Model};
+
+    #[test]
+    fn test_parse_override() {
+        let scale: Override = "scale=0.2".parse().unwrap();
+        assert_eq!(scale.node, None);
+        assert_eq!(scale.name, "scale");
+        assert_eq!(scale.value, "0.2");
+
+        let subdivisions: Override = "#0.subdivisions=12".parse().unwrap();
+        assert_eq!(subdivisions.node, Some(NodeId::from_raw(0)));
+        assert_eq!(subdivisions.name, "subdivisions");
+
+        assert!("scale".parse::<Override>().is_err());
+        assert!("sphere.subdivisions=12".parse::<Override>().is_err());
+    }
+
+    #[test]
+    fn test_apply_override() {
+        let mut node_graph = example().unwrap();
+        let sphere = node_graph.get_node_ids()[0];
+        let scale = node_graph.get_node_ids()[2];
+        let before = node_graph.get_output().unwrap();
+
+        // the scale of the instances is an input of the scale node, the subdivisions a parameter of the sphere
+        let applied = "scale=0.2"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut node_graph)
+            .unwrap();
+        assert_eq!(applied, 1);
+        assert_eq!(
+            node_graph.get_input_default(&NodeSocket::new(scale, 1)),
+            Some(ParameterValue::Float(0.2))
+        );
+        "0.subdivisions=3"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut node_graph)
+            .unwrap();
+        assert_eq!(
+            node_graph.get_parameter(sphere, "subdivisions"),
+            Some(ParameterValue::Int(3))
+        );
+        let after: Model = node_graph.get_output().unwrap();
+        assert!(after.triangle_count() < before.triangle_count());
+
+        let not_found = "radius=2"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut node_graph);
+        assert!(matches!(not_found, Err(OverrideError::NotFound { .. })));
+        let invalid = "subdivisions=many"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut node_graph);
+        assert!(matches!(invalid, Err(OverrideError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_override_sets_nothing_on_invalid_value() {
+        let mut node_graph = NodeGraph::new();
+        let float = node_graph.add_node(ValueNode::new(1.0_f32));
+        node_graph.add_node(ValueNode::new(false));
+
+        let result = "value=3"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut node_graph);
+        assert!(matches!(result, Err(OverrideError::InvalidValue { .. })));
+        assert_eq!(
+            node_graph.get_parameter(float, "value"),
+            Some(ParameterValue::Float(1.0))
+        );
+    }
+}
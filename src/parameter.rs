@@ -0,0 +1,163 @@
+use std::any::{Any, TypeId};
+
+use serde::{Deserialize, Serialize};
+use three_d::Vector3;
+
+// a value that is part of the state of a node, like the value of a ValueNode
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ParameterValue {
+    Float(f32),
+    Int(i32),
+    Vector3([f32; 3]),
+    Bool(bool),
+    String(String),
+    // one of a fixed list of options, like the variants of an enum
+    Enum {
+        selected: String,
+        options: Vec<String>,
+    },
+}
+
+impl ParameterValue {
+    pub fn enumeration(selected: &str, options: &[&str]) -> Self {
+        ParameterValue::Enum {
+            selected: selected.to_string(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+        }
+    }
+
+    // the value as the type a socket of this type receives
+    pub fn to_any(&self) -> Box<dyn Any + Send + Sync> {
+        match self {
+            ParameterValue::Float(value) => Box::new(*value),
+            ParameterValue::Int(value) => Box::new(*value),
+            ParameterValue::Vector3([x, y, z]) => Box::new(Vector3::new(*x, *y, *z)),
+            ParameterValue::Bool(value) => Box::new(*value),
+            ParameterValue::String(value) => Box::new(value.clone()),
+            ParameterValue::Enum { selected, .. } => Box::new(selected.clone()),
+        }
+    }
+
+    // the type id of the value returned by `to_any`
+    pub fn value_type_id(&self) -> TypeId {
+        match self {
+            ParameterValue::Float(_) => TypeId::of::<f32>(),
+            ParameterValue::Int(_) => TypeId::of::<i32>(),
+            ParameterValue::Vector3(_) => TypeId::of::<Vector3<f32>>(),
+            ParameterValue::Bool(_) => TypeId::of::<bool>(),
+            ParameterValue::String(_) | ParameterValue::Enum { .. } => TypeId::of::<String>(),
+        }
+    }
+
+    // reads text as a value of the same kind as this one, like `0.2` for a float or `1,0,2` for a vector
+    pub fn parse_as(&self, text: &str) -> Option<ParameterValue> {
+        let text = text.trim();
+        match self {
+            ParameterValue::Float(_) => text.parse().ok().map(ParameterValue::Float),
+            ParameterValue::Int(_) => text.parse().ok().map(ParameterValue::Int),
+            ParameterValue::Vector3(_) => {
+                let components = text
+                    .split(',')
+                    .map(|component| component.trim().parse().ok())
+                    .collect::<Option<Vec<f32>>>()?;
+                let [x, y, z] = components[..] else {
+                    return None;
+                };
+                Some(ParameterValue::Vector3([x, y, z]))
+            }
+            ParameterValue::Bool(_) => text.parse().ok().map(ParameterValue::Bool),
+            ParameterValue::String(_) => Some(ParameterValue::String(text.to_string())),
+            ParameterValue::Enum { options, .. } => {
+                options
+                    .contains(&text.to_string())
+                    .then(|| ParameterValue::Enum {
+                        selected: text.to_string(),
+                        options: options.clone(),
+                    })
+            }
+        }
+    }
+}
+
+// a type that can be stored as a parameter
+pub trait ParameterType: Clone + Send + Sync + 'static {
+    // a short name for the type, used in the identifiers of generic nodes
+    const NAME: &'static str;
+    fn to_parameter(&self) -> ParameterValue;
+    fn from_parameter(value: &ParameterValue) -> Option<Self>;
+}
+
+impl ParameterType for f32 {
+    const NAME: &'static str = "f32";
+
+    fn to_parameter(&self) -> ParameterValue {
+        ParameterValue::Float(*self)
+    }
+
+    fn from_parameter(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl ParameterType for i32 {
+    const NAME: &'static str = "i32";
+
+    fn to_parameter(&self) -> ParameterValue {
+        ParameterValue::Int(*self)
+    }
+
+    fn from_parameter(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl ParameterType for Vector3<f32> {
+    const NAME: &'static str = "vector3";
+
+    fn to_parameter(&self) -> ParameterValue {
+        ParameterValue::Vector3([self.x, self.y, self.z])
+    }
+
+    fn from_parameter(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Vector3([x, y, z]) => Some(Vector3::new(*x, *y, *z)),
+            _ => None,
+        }
+    }
+}
+
+impl ParameterType for bool {
+    const NAME: &'static str = "bool";
+
+    fn to_parameter(&self) -> ParameterValue {
+        ParameterValue::Bool(*self)
+    }
+
+    fn from_parameter(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl ParameterType for String {
+    const NAME: &'static str = "string";
+
+    fn to_parameter(&self) -> ParameterValue {
+        ParameterValue::String(self.clone())
+    }
+
+    fn from_parameter(value: &ParameterValue) -> Option<Self> {
+        match value {
+            ParameterValue::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::{Model, NodeId, Value};
+
+// what was measured for a node while profiling
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeStats {
+    pub name: String,
+    // how many times the node was run
+    pub calls: u32,
+    // how many times the cached outputs of the node were used instead of running it
+    pub cache_hits: u32,
+    pub total_time: Duration,
+    pub last_time: Duration,
+    // the size of the models the node output the last time it ran
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
+impl NodeStats {
+    pub fn average_time(&self) -> Duration {
+        match self.calls {
+            0 => Duration::ZERO,
+            calls => self.total_time / calls,
+        }
+    }
+}
+
+impl fmt::Display for NodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.2} ms, {} calls, {} cache hits",
+            self.total_time.as_secs_f64() * 1000.0,
+            self.calls,
+            self.cache_hits
+        )?;
+        if self.vertices > 0 {
+            write!(
+                f,
+                ", {} vertices, {} triangles",
+                self.vertices, self.triangles
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// the statistics of every node since profiling was turned on
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileReport {
+    pub nodes: BTreeMap<NodeId, NodeStats>,
+}
+
+impl ProfileReport {
+    // the time spent running nodes, nodes running at the same time are counted separately
+    pub fn total_time(&self) -> Duration {
+        self.nodes.values().map(|stats| stats.total_time).sum()
+    }
+
+    // the nodes ordered from the one that took the longest in total
+    pub fn slowest(&self) -> Vec<(NodeId, &NodeStats)> {
+        let mut nodes: Vec<(NodeId, &NodeStats)> =
+            self.nodes.iter().map(|(id, stats)| (*id, stats)).collect();
+        nodes.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.total_time));
+        nodes
+    }
+
+    pub(crate) fn record_run(
+        &mut self,
+        node: NodeId,
+        name: &str,
+        time: Duration,
+        output: &[Value],
+    ) {
+        let stats = self.stats_mut(node, name);
+        stats.calls += 1;
+        stats.total_time += time;
+        stats.last_time = time;
+        (stats.vertices, stats.triangles) = model_size(output);
+    }
+
+    pub(crate) fn record_cache_hit(&mut self, node: NodeId, name: &str) {
+        self.stats_mut(node, name).cache_hits += 1;
+    }
+
+    fn stats_mut(&mut self, node: NodeId, name: &str) -> &mut NodeStats {
+        self.nodes.entry(node).or_insert_with(|| NodeStats {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, stats) in self.slowest() {
+            writeln!(f, "{} {}: {}", stats.name, id, stats)?;
+        }
+        Ok(())
+    }
+}
+
+// the vertices and triangles of the models among the outputs
+fn model_size(output: &[Value]) -> (usize, usize) {
+    let mut vertices = 0;
+    let mut triangles = 0;
+    let mut add = |model: &Model| {
+        vertices += model.vertices.len();
+        triangles += model.indices.len() / 3;
+    };
+    for value in output {
+        if let Some(model) = value.downcast_ref::<Model>() {
+            add(model);
+        } else if let Some(models) = value.downcast_ref::<Vec<Model>>() {
+            models.iter().for_each(&mut add);
+        }
+    }
+    (vertices, triangles)
+}
@@ -0,0 +1,148 @@
+use std::any::TypeId;
+use std::collections::BTreeMap;
+
+use crate::group::{GroupNode, SharedGroup};
+use crate::macros::{InputOrOutput, Output};
+use crate::node::{input_sockets, node_name, output_sockets};
+use crate::socket::Socket;
+use crate::{box_node, Node, NodeAny};
+
+// everything known about a type of node without needing an instance of it
+pub struct NodeType {
+    identifier: String,
+    name: String,
+    description: String,
+    inputs: Vec<Socket>,
+    outputs: Vec<Socket>,
+    factory: Box<dyn Fn() -> Box<dyn NodeAny> + Send + Sync>,
+}
+
+impl NodeType {
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn inputs(&self) -> &[Socket] {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &[Socket] {
+        &self.outputs
+    }
+
+    pub fn input_types(&self) -> Vec<TypeId> {
+        self.inputs.iter().map(|socket| socket.type_id).collect()
+    }
+
+    pub fn output_types(&self) -> Vec<TypeId> {
+        self.outputs.iter().map(|socket| socket.type_id).collect()
+    }
+
+    pub fn create(&self) -> Box<dyn NodeAny> {
+        (self.factory)()
+    }
+}
+
+// the node types that can be created by their identifier, like when loading a graph
+pub struct NodeRegistry {
+    node_types: BTreeMap<String, NodeType>,
+}
+
+impl NodeRegistry {
+    // a registry without any node types, use `default` to get the built-in nodes
+    pub fn new() -> Self {
+        Self {
+            node_types: BTreeMap::new(),
+        }
+    }
+
+    // registers a node type under the identifier of the node, replacing any type registered before
+    pub fn register<N, I, O>(&mut self, factory: impl Fn() -> N + Send + Sync + 'static)
+    where
+        I: InputOrOutput<T = I> + 'static,
+        O: Output<T = O> + 'static,
+        N: Node<I, O> + 'static,
+    {
+        let node_type = NodeType {
+            identifier: N::identifier(),
+            name: node_name::<N, I, O>(),
+            description: N::description(),
+            inputs: input_sockets::<N, I, O>(),
+            outputs: output_sockets::<N, I, O>(),
+            factory: Box::new(move || box_node(factory())),
+        };
+        self.node_types
+            .insert(node_type.identifier.clone(), node_type);
+    }
+
+    // registers a group so that instances of it can be created, they all share the definition
+    pub fn register_group(&mut self, definition: SharedGroup) {
+        let sample = GroupNode::new(definition.clone());
+        let node_type = NodeType {
+            identifier: sample.identifier().to_string(),
+            name: sample.name().to_string(),
+            description: sample.description().to_string(),
+            inputs: sample.input_sockets(),
+            outputs: sample.output_sockets(),
+            factory: Box::new(move || Box::new(GroupNode::new(definition.clone()))),
+        };
+        self.node_types
+            .insert(node_type.identifier.clone(), node_type);
+    }
+
+    // the definition of a registered group
+    pub fn group(&self, identifier: &str) -> Option<SharedGroup> {
+        self.create(identifier)?
+            .as_group()
+            .map(|group| group.definition().clone())
+    }
+
+    pub fn get(&self, identifier: &str) -> Option<&NodeType> {
+        self.node_types.get(identifier)
+    }
+
+    pub fn create(&self, identifier: &str) -> Option<Box<dyn NodeAny>> {
+        self.get(identifier).map(NodeType::create)
+    }
+
+    // every registered node type, sorted by identifier
+    pub fn node_types(&self) -> impl Iterator<Item = &NodeType> {
+        self.node_types.values()
+    }
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        crate::nodes::register_nodes(&mut registry);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Model, SphereNode};
+
+    #[test]
+    fn test_default_registry() {
+        let registry = NodeRegistry::default();
+        let sphere = registry.get("sphere").unwrap();
+        assert_eq!(sphere.name(), "Sphere");
+        assert!(sphere.input_types().is_empty());
+        assert_eq!(sphere.output_types(), vec![TypeId::of::<Model>()]);
+        assert_eq!(sphere.outputs()[0].name, "Sphere");
+
+        let node = registry.create("sphere").unwrap();
+        assert_eq!(node.node_type_id(), TypeId::of::<SphereNode>());
+        assert!(registry.create("teapot").is_none());
+    }
+}
@@ -1,43 +1,68 @@
 use eframe::{
     egui::{
-        load::{Bytes, SizedTexture},
-        pos2, vec2, Area, CentralPanel, Color32, Frame, Id, ImageSource, Pos2, Rect, Rounding,
-        Sense, Shadow, Vec2,
+        pos2, vec2, Align2, Area, CentralPanel, Color32, ComboBox, DragValue, Frame, Id, Label,
+        Pos2, Rect, RichText, Sense, Shadow, Shape, TextEdit, TopBottomPanel, Ui, Vec2,
     },
-    run_native, App, HardwareAcceleration, NativeOptions, Renderer,
+    run_native, App, HardwareAcceleration, NativeOptions,
 };
 use three_d::*;
 
-use crate::{NodeAny, NodeGraph};
-
+use crate::diagnostics::{Diagnostic, Level};
+use crate::geometry::{Curve, PointCloud};
+use crate::graph_file::{GraphDescription, GroupDescription};
+use crate::group::{GroupDefinition, SharedGroup};
+use crate::parameter::ParameterValue;
+use crate::registry::NodeRegistry;
+use crate::socket::Socket;
+use crate::{FileError, GraphError, Model, NodeAny, NodeGraph, NodeId, NodeSocket, Value};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
 use std::hash::Hasher;
-use std::{any::Any, hash::Hash};
+
+const DEFAULT_POSITION: Pos2 = pos2(200.0, 200.0);
+const DEFAULT_SIZE: Vec2 = vec2(100.0, 100.0);
 
 struct ColorScheme {
     background: Color32,
     node_background: Color32,
     node_text: Color32,
+    // the outline of selected nodes
+    selection: Color32,
+    // the badge on nodes that failed
+    error: Color32,
+    // the warnings emitted by nodes
+    warning: Color32,
 }
 
 // a node graph but with extra information for rendering
 struct VisualNodeGraph {
     node_graph: NodeGraph,
-    node_inputs: Vec<Vec<Rect>>,
-    node_outputs: Vec<Vec<Rect>>,
-    positions: Vec<Pos2>,
-    sizes: Vec<eframe::egui::Vec2>,
+    node_inputs: HashMap<NodeId, Vec<Rect>>,
+    node_outputs: HashMap<NodeId, Vec<Rect>>,
+    positions: HashMap<NodeId, Pos2>,
+    sizes: HashMap<NodeId, eframe::egui::Vec2>,
     scheme: ColorScheme,
+    // the node types that can be added from the editor
+    registry: NodeRegistry,
+    // the result of the last evaluation of the graph, the model of every output by name
+    output: Option<Result<BTreeMap<String, Value>, GraphError>>,
+    // the nodes that are grouped together by "Group selected nodes"
+    selected: HashSet<NodeId>,
 }
 
 impl VisualNodeGraph {
     fn new(node_graph: NodeGraph, scheme: ColorScheme) -> Self {
         let mut new = VisualNodeGraph {
             node_graph,
-            node_inputs: Vec::new(),
-            node_outputs: Vec::new(),
-            positions: Vec::new(),
-            sizes: Vec::new(),
+            node_inputs: HashMap::new(),
+            node_outputs: HashMap::new(),
+            positions: HashMap::new(),
+            sizes: HashMap::new(),
             scheme,
+            registry: NodeRegistry::default(),
+            output: None,
+            selected: HashSet::new(),
         };
 
         new.init();
@@ -45,41 +70,212 @@ impl VisualNodeGraph {
         new
     }
 
+    fn get_node_status(&self, id: NodeId) -> NodeStatus {
+        NodeStatus {
+            defaults: self.get_shown_defaults(id),
+            error: self
+                .node_graph
+                .get_node_error(id)
+                .map(|error| self.node_graph.describe_error(error)),
+            selected: self.selected.contains(&id),
+            diagnostics: self.node_graph.get_node_diagnostics(id).to_vec(),
+            stats: self
+                .node_graph
+                .profile_report()
+                .and_then(|profile| profile.nodes.get(&id))
+                .map(|stats| stats.to_string()),
+        }
+    }
+
+    fn set_profiling(&mut self, profiling: bool) {
+        self.node_graph.set_profiling(profiling);
+        // evaluate everything again so every node gets measured
+        if profiling {
+            for id in self.node_graph.get_node_ids() {
+                self.node_graph.mark_dirty(id);
+            }
+            self.evaluate();
+        }
+    }
+
     fn setup_positions(&mut self) {
-        const DEFAULT_POSITION: Pos2 = pos2(200.0, 200.0);
-        const DEFAULT_SIZE: Vec2 = vec2(100.0, 100.0);
-        let mut positions = vec![DEFAULT_POSITION; self.node_graph.get_nodes().len()];
-        let mut sizes = vec![DEFAULT_SIZE; self.node_graph.get_nodes().len()];
-        self.positions = positions;
-        self.sizes = sizes;
+        for id in self.node_graph.get_node_ids() {
+            self.positions.insert(id, DEFAULT_POSITION);
+            self.sizes.insert(id, DEFAULT_SIZE);
+        }
     }
 
-    fn get_node_position(&self, node_index: usize) -> Pos2 {
-        self.positions[node_index]
+    fn get_node_position(&self, id: NodeId) -> Pos2 {
+        self.positions.get(&id).copied().unwrap_or(DEFAULT_POSITION)
     }
 
-    fn set_node_position(&mut self, node_index: usize, position: Pos2) {
-        self.positions[node_index] = position;
+    fn set_node_position(&mut self, id: NodeId, position: Pos2) {
+        self.positions.insert(id, position);
     }
 
-    fn get_node_size(&self, node_index: usize) -> eframe::egui::Vec2 {
-        self.sizes[node_index]
+    #[allow(dead_code)]
+    fn get_node_size(&self, id: NodeId) -> eframe::egui::Vec2 {
+        self.sizes.get(&id).copied().unwrap_or(DEFAULT_SIZE)
     }
 
-    fn set_node_size(&mut self, node_index: usize, size: eframe::egui::Vec2) {
-        self.sizes[node_index] = size;
+    fn set_node_size(&mut self, id: NodeId, size: eframe::egui::Vec2) {
+        self.sizes.insert(id, size);
     }
 
     fn init(&mut self) {
         self.setup_positions();
+        self.evaluate();
     }
 
+    fn evaluate(&mut self) {
+        self.output = Some(self.node_graph.get_outputs());
+    }
+
+    // describes the graph along with where its nodes are shown
+    fn to_description(&self) -> GraphDescription {
+        let mut description = self.node_graph.to_description();
+        for node in description.nodes.iter_mut() {
+            let position = self.get_node_position(node.id);
+            node.position = Some([position.x, position.y]);
+        }
+        description
+    }
+
+    // replaces the graph with the one in the description
+    fn load(&mut self, description: &GraphDescription) -> Result<(), FileError> {
+        self.node_graph =
+            NodeGraph::from_description_with_registry(description, &mut self.registry)?;
+        self.positions.clear();
+        self.sizes.clear();
+        self.node_inputs.clear();
+        self.node_outputs.clear();
+        self.selected.clear();
+        self.init();
+
+        for node in description.nodes.iter() {
+            if let Some([x, y]) = node.position {
+                self.set_node_position(node.id, pos2(x, y));
+            }
+        }
+        Ok(())
+    }
+
+    // adds a node of a registered type at a position
+    fn add_node(&mut self, identifier: &str, position: Pos2) {
+        let Some(node) = self.registry.create(identifier) else {
+            return;
+        };
+
+        let Ok(id) = self.node_graph.add_dyn_node(node) else {
+            return;
+        };
+        self.set_node_position(id, position);
+        self.evaluate();
+    }
+
+    // the defaults shown on the node, for every input socket that isn't connected
+    fn get_shown_defaults(&self, id: NodeId) -> Vec<Option<ParameterValue>> {
+        let Some(node) = self.node_graph.get_node(id) else {
+            return Vec::new();
+        };
+
+        (0..node.needed_types_input().len())
+            .map(|socket| {
+                let socket = NodeSocket::new(id, socket);
+                match self.node_graph.get_input_connection(&socket) {
+                    Some(_) => None,
+                    None => self.node_graph.get_input_default(&socket),
+                }
+            })
+            .collect()
+    }
+
+    fn set_input_default(&mut self, socket: NodeSocket, value: ParameterValue) {
+        if self.node_graph.set_input_default(&socket, value).is_ok() {
+            self.evaluate();
+        }
+    }
+
+    fn set_parameter(&mut self, id: NodeId, name: &str, value: ParameterValue) {
+        if self.node_graph.set_parameter(id, name, value) {
+            self.evaluate();
+        }
+    }
+
+    // removes a node along with everything the renderer keeps for it
+    fn remove_node(&mut self, id: NodeId) {
+        if self.node_graph.remove_node(id).is_err() {
+            return;
+        }
+
+        self.positions.remove(&id);
+        self.sizes.remove(&id);
+        self.node_inputs.remove(&id);
+        self.node_outputs.remove(&id);
+        self.selected.remove(&id);
+
+        self.evaluate();
+    }
+
+    fn toggle_selected(&mut self, id: NodeId) {
+        if !self.selected.remove(&id) {
+            self.selected.insert(id);
+        }
+    }
+
+    // collapses the selected nodes into a new group, which can then be added like any other node
+    fn group_selected(&mut self) -> Result<(), GraphError> {
+        let nodes: Vec<NodeId> = self.selected.drain().collect();
+        if nodes.is_empty() {
+            return Ok(());
+        }
+
+        let mut number = 1;
+        while self
+            .registry
+            .get(&format!("group_Group {}", number))
+            .is_some()
+        {
+            number += 1;
+        }
+
+        // the group takes the place of the nodes in it
+        let count = nodes.len() as f32;
+        let center = nodes.iter().fold(Pos2::ZERO, |center, &id| {
+            center + self.get_node_position(id).to_vec2() / count
+        });
+
+        let (group, definition) = self
+            .node_graph
+            .group_nodes(&nodes, &format!("Group {}", number))?;
+        self.registry.register_group(definition);
+
+        for id in nodes {
+            self.positions.remove(&id);
+            self.sizes.remove(&id);
+            self.node_inputs.remove(&id);
+            self.node_outputs.remove(&id);
+        }
+        self.set_node_position(group, center);
+        self.evaluate();
+        Ok(())
+    }
+
+    // makes the groups in a file available to be added
+    fn load_group(&mut self, description: &GroupDescription) -> Result<(), FileError> {
+        let definition = GroupDefinition::from_description(description, &mut self.registry)?;
+        self.registry.register_group(definition);
+        Ok(())
+    }
+
+    // pushes overlapping nodes apart, not used by the editor yet
+    #[allow(dead_code)]
     fn step(&mut self) {
         const SPEED: f32 = 0.03;
 
         // move the nodes away from each other
-        for i in 0..self.node_graph.get_nodes().len() {
-            for j in 0..self.node_graph.get_nodes().len() {
+        for i in self.node_graph.get_node_ids() {
+            for j in self.node_graph.get_node_ids() {
                 if i == j {
                     continue;
                 }
@@ -91,8 +287,6 @@ impl VisualNodeGraph {
                 let size_i = self.get_node_size(i);
                 let size_j = self.get_node_size(j);
 
-                println!("{} {}", size_i.x, size_i.y);
-
                 let i_right = pos_i.x + size_i.x;
                 let i_left = pos_i.x;
                 let i_bottom = pos_i.y + size_i.y;
@@ -117,25 +311,31 @@ impl VisualNodeGraph {
         }
     }
 
-    fn get_input_rect(&self, node_index: usize, input_index: usize) -> Rect {
-        self.node_inputs[node_index][input_index]
+    fn get_input_rect(&self, id: NodeId, input_index: usize) -> Option<Rect> {
+        self.node_inputs.get(&id)?.get(input_index).copied()
     }
 
-    fn get_output_rect(&self, node_index: usize, output_index: usize) -> Rect {
-        self.node_outputs[node_index][output_index]
+    fn get_output_rect(&self, id: NodeId, output_index: usize) -> Option<Rect> {
+        self.node_outputs.get(&id)?.get(output_index).copied()
     }
-
-    fn show() {}
 }
 
 struct NodeGraphRenderer {
     visual_node_graph: VisualNodeGraph,
     was_dragging: bool,
+    // the file the graph is saved to and loaded from
+    file_path: String,
+    // the result of the last save or load
+    file_status: Option<String>,
+    // where a node added from the menu is placed
+    new_node_position: Pos2,
+    // whether the time every node takes is shown on it
+    profiling: bool,
     //three_d_info: ThreeDInfo,
 }
 
 impl App for NodeGraphRenderer {
-    fn update(&mut self, ctx: &eframe::egui::Context, frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
         /*
         let context = &self.three_d_info.context;
 
@@ -152,6 +352,27 @@ impl App for NodeGraphRenderer {
         ctx.include_bytes("color", bytes);
         */
 
+        TopBottomPanel::top("file").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.file_path).hint_text("graph.ron"));
+                if ui.button("Save").clicked() {
+                    self.save();
+                }
+                if ui.button("Load").clicked() {
+                    self.load();
+                }
+                if ui.button("Load group").clicked() {
+                    self.load_group();
+                }
+                if ui.checkbox(&mut self.profiling, "Profile").changed() {
+                    self.visual_node_graph.set_profiling(self.profiling);
+                }
+                if let Some(status) = &self.file_status {
+                    ui.label(status);
+                }
+            });
+        });
+
         CentralPanel::default()
             .frame(Frame::default().fill(self.visual_node_graph.scheme.background))
             .show(ctx, |ui| {
@@ -166,36 +387,107 @@ impl App for NodeGraphRenderer {
                     //self.visual_node_graph.step();
                 }
 
+                let background = ui.interact(ui.max_rect(), Id::new("background"), Sense::click());
+                if background.secondary_clicked() {
+                    if let Some(position) = background.interact_pointer_pos() {
+                        self.new_node_position = position;
+                    }
+                }
+
+                let mut added_node = None;
+                let mut group_selected = false;
+                let has_selection = !self.visual_node_graph.selected.is_empty();
+                background.context_menu(|ui| {
+                    if has_selection {
+                        if ui.button("Group selected nodes").clicked() {
+                            group_selected = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                    }
+
+                    for node_type in self.visual_node_graph.registry.node_types() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button(node_type.name())
+                                .on_hover_text(node_type.description())
+                                .clicked()
+                            {
+                                added_node = Some(node_type.identifier().to_string());
+                                ui.close_menu();
+                            }
+                            ui.weak(node_type.identifier());
+                        });
+                    }
+                });
+
+                if let Some(identifier) = added_node {
+                    self.visual_node_graph
+                        .add_node(&identifier, self.new_node_position);
+                }
+
+                if group_selected {
+                    if let Err(error) = self.visual_node_graph.group_selected() {
+                        self.file_status =
+                            Some(self.visual_node_graph.node_graph.describe_error(&error));
+                    }
+                }
+
+                let mut removed_node = None;
+
                 // add a node to the graph
-                for i in 0..self.visual_node_graph.node_graph.get_nodes().len() {
-                    let node = self.visual_node_graph.node_graph.get_node(i);
+                for id in self.visual_node_graph.node_graph.get_node_ids() {
+                    let Some(node) = self.visual_node_graph.node_graph.get_node(id) else {
+                        continue;
+                    };
 
                     let response = show_node(
+                        id,
                         node,
-                        self.visual_node_graph.get_node_position(i),
+                        self.visual_node_graph.get_node_status(id),
+                        self.visual_node_graph.get_node_position(id),
                         ctx,
                         &self.visual_node_graph.scheme,
                     );
 
-                    self.visual_node_graph.set_node_position(i, response.pos);
-                    self.visual_node_graph.set_node_size(i, response.size);
-                    if i >= self.visual_node_graph.node_inputs.len() {
+                    for (socket, value) in response.edited_defaults {
                         self.visual_node_graph
-                            .node_inputs
-                            .push(response.input_rects.clone());
-                        self.visual_node_graph
-                            .node_outputs
-                            .push(response.output_rects.clone());
-                    } else {
-                        self.visual_node_graph.node_inputs[i] = response.input_rects.clone();
-                        self.visual_node_graph.node_outputs[i] = response.output_rects.clone();
+                            .set_input_default(NodeSocket::new(id, socket), value);
+                    }
+                    for (name, value) in response.edited_parameters {
+                        self.visual_node_graph.set_parameter(id, &name, value);
+                    }
+
+                    self.visual_node_graph.set_node_position(id, response.pos);
+                    self.visual_node_graph.set_node_size(id, response.size);
+                    self.visual_node_graph
+                        .node_inputs
+                        .insert(id, response.input_rects);
+                    self.visual_node_graph
+                        .node_outputs
+                        .insert(id, response.output_rects);
+
+                    if response.removed {
+                        removed_node = Some(id);
+                    }
+                    if response.toggled_selection {
+                        self.visual_node_graph.toggle_selected(id);
+                    }
+                    if let Some(group) = response.saved_group {
+                        self.save_group(&group);
                     }
                 }
 
+                if let Some(id) = removed_node {
+                    self.visual_node_graph.remove_node(id);
+                }
+
                 for connection in self.visual_node_graph.node_graph.get_connections() {
                     render_connection(&self.visual_node_graph, &connection, ui);
                 }
 
+                show_output_status(&self.visual_node_graph, ui);
+
                 //ui.image(ImageSource::Uri("color".into()));
             });
 
@@ -204,29 +496,131 @@ impl App for NodeGraphRenderer {
     }
 }
 
+impl NodeGraphRenderer {
+    fn save(&mut self) {
+        let result = self
+            .visual_node_graph
+            .to_description()
+            .save(&self.file_path);
+        self.file_status = Some(match result {
+            Ok(()) => format!("saved {}", self.file_path),
+            Err(error) => error.to_string(),
+        });
+    }
+
+    // saves the definition of a group to the file path, so it can be loaded into other graphs
+    fn save_group(&mut self, group: &SharedGroup) {
+        let result = group.read().to_description().save(&self.file_path);
+        self.file_status = Some(match result {
+            Ok(()) => format!("saved {}", self.file_path),
+            Err(error) => error.to_string(),
+        });
+    }
+
+    fn load_group(&mut self) {
+        let result = GroupDescription::load(&self.file_path)
+            .and_then(|description| self.visual_node_graph.load_group(&description));
+        self.file_status = Some(match result {
+            Ok(()) => format!("loaded group {}", self.file_path),
+            Err(error) => error.to_string(),
+        });
+    }
+
+    fn load(&mut self) {
+        let result = GraphDescription::load(&self.file_path)
+            .and_then(|description| self.visual_node_graph.load(&description));
+        self.file_status = Some(match result {
+            Ok(()) => format!("loaded {}", self.file_path),
+            Err(error) => error.to_string(),
+        });
+    }
+}
+
 fn render_connection(
     visual_node_graph: &VisualNodeGraph,
     connection: &crate::Connection,
     ui: &mut eframe::egui::Ui,
 ) {
-    let start =
-        visual_node_graph.get_output_rect(connection.from().node(), connection.from().socket());
-
-    let end = visual_node_graph.get_input_rect(connection.to().node(), connection.to().socket());
+    let (Some(start), Some(end), Some(node)) = (
+        visual_node_graph.get_output_rect(connection.from().node(), connection.from().socket()),
+        visual_node_graph.get_input_rect(connection.to().node(), connection.to().socket()),
+        visual_node_graph
+            .node_graph
+            .get_node(connection.from().node()),
+    ) else {
+        return;
+    };
 
-    let mut start = start.center();
-    let mut end = end.center();
+    let start = start.center();
+    let end = end.center();
 
     let painter = ui.painter();
-    let node = visual_node_graph
-        .node_graph
-        .get_node(connection.from().node());
 
     let type_id = node.needed_types_output()[connection.from().socket()];
 
     let color = hash_type_id(type_id);
 
-    painter.line_segment([start, end], (1.0, color));
+    // converted values are drawn dashed so they stand out from plain connections
+    if visual_node_graph.node_graph.is_converted(connection) {
+        painter.extend(Shape::dashed_line(&[start, end], (1.0, color), 6.0, 4.0));
+    } else {
+        painter.line_segment([start, end], (1.0, color));
+    }
+}
+
+fn show_output_status(visual_node_graph: &VisualNodeGraph, ui: &mut eframe::egui::Ui) {
+    let (text, color) = match &visual_node_graph.output {
+        Some(Ok(outputs)) => (
+            outputs
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, describe_output(value)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            visual_node_graph.scheme.node_text,
+        ),
+        Some(Err(error)) => (
+            visual_node_graph.node_graph.describe_error(error),
+            Color32::RED,
+        ),
+        None => return,
+    };
+
+    ui.painter().text(
+        ui.max_rect().left_bottom() + vec2(8.0, -8.0),
+        Align2::LEFT_BOTTOM,
+        text,
+        Default::default(),
+        color,
+    );
+}
+
+// what is shown on a node besides its sockets
+struct NodeStatus {
+    defaults: Vec<Option<ParameterValue>>,
+    // the error of the node from the last evaluation, described for the user
+    error: Option<String>,
+    selected: bool,
+    // the messages the node emitted the last time it ran
+    diagnostics: Vec<Diagnostic>,
+    // what was measured for the node while profiling
+    stats: Option<String>,
+}
+
+// a short summary of the value of an output, by its type
+fn describe_output(value: &Value) -> String {
+    if let Some(model) = value.downcast_ref::<Model>() {
+        format!("{} vertices", model.vertices.len())
+    } else if let Some(models) = value.downcast_ref::<Vec<Model>>() {
+        format!("{} instances", models.len())
+    } else if let Some(points) = value.downcast_ref::<PointCloud>() {
+        format!("{} points", points.len())
+    } else if let Some(curve) = value.downcast_ref::<Curve>() {
+        format!("a curve of length {:.2}", curve.length())
+    } else if let Some(scalar) = value.downcast_ref::<f32>() {
+        format!("{:.3}", scalar)
+    } else {
+        "a value".to_string()
+    }
 }
 
 struct NodeResponse {
@@ -234,10 +628,20 @@ struct NodeResponse {
     size: Vec2,
     input_rects: Vec<Rect>,
     output_rects: Vec<Rect>,
+    removed: bool,
+    toggled_selection: bool,
+    // the definition of the group to save, if the node is a group
+    saved_group: Option<SharedGroup>,
+    // the input sockets whose default was changed, with the new value
+    edited_defaults: Vec<(usize, ParameterValue)>,
+    // the parameters that were changed, with the new value
+    edited_parameters: Vec<(String, ParameterValue)>,
 }
 
 fn show_node(
+    id: NodeId,
     node: &dyn NodeAny,
+    status: NodeStatus,
     pos: Pos2,
     ctx: &eframe::egui::Context,
     scheme: &ColorScheme,
@@ -247,6 +651,10 @@ fn show_node(
         .rounding(12.0)
         .inner_margin(12.0)
         .fill(scheme.node_background)
+        .stroke(match status.selected {
+            true => (2.0, scheme.selection),
+            false => (0.0, Color32::TRANSPARENT),
+        })
         .shadow(Shadow {
             offset: vec2(0.0, 0.0),
             blur: 4.0,
@@ -254,69 +662,113 @@ fn show_node(
             color: Color32::BLACK,
         });
 
-    let area = Area::new(Id::new(node.name()))
-        .current_pos(pos)
-        .movable(true);
+    let area = Area::new(Id::new(id)).current_pos(pos).movable(true);
 
     let mut input_rects = Vec::new();
     let mut output_rects = Vec::new();
+    let mut removed = false;
+    let mut toggled_selection = false;
+    let mut saved_group = None;
+    let mut edited_defaults = Vec::new();
+    let mut edited_parameters = Vec::new();
 
     let response = area.show(ctx, |ui| {
         // display a number of spheres equal to the number of inputs on the left of the node
         ui.horizontal(|ui| {
-            let input_response = ui.vertical(|ui| {
-                for needed_type in node.needed_types_input() {
-                    let (rect, painter) =
-                        ui.allocate_painter(Vec2::new(10.0, 10.0), Sense::hover());
-
-                    let center = rect.rect.center();
-                    let radius = 5.0;
-                    // hash the type id to get a color
-                    let color = hash_type_id(needed_type);
-
-                    painter.circle_filled(center, radius, color);
+            ui.vertical(|ui| {
+                for (i, socket) in node.input_sockets().into_iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        input_rects.push(show_socket(ui, &socket));
+                        ui.label(RichText::new(&socket.name).color(scheme.node_text).small());
+
+                        // unconnected sockets can be edited in place
+                        if let Some(Some(default)) = status.defaults.get(i) {
+                            let mut value = default.clone();
+                            if edit_parameter(ui, &mut value) {
+                                edited_defaults.push((i, value));
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(format!("{}: {}", socket.type_name, socket.description));
                 }
             });
-            let all_rect = input_response.response.rect;
-            // split the rect into the individual input rects
-            for i in 0..node.needed_types_input().len() {
-                let rect = Rect::from_min_max(
-                    all_rect.min + vec2(0.0, i as f32 * 10.0),
-                    all_rect.min + vec2(10.0, (i + 1) as f32 * 10.0),
-                );
-                input_rects.push(rect);
-            }
 
             container.show(ui, |ui| {
-                ui.label(node.name()).on_hover_text(node.description());
-            });
-            let output_response = ui.vertical(|ui| {
-                for needed_type in node.needed_types_output() {
-                    if node.name() == "Output" {
-                        continue;
-                    }
+                // a badge on nodes that failed, the message is shown when hovering it
+                if let Some(error) = &status.error {
+                    ui.label(
+                        RichText::new(" ! ")
+                            .strong()
+                            .color(Color32::WHITE)
+                            .background_color(scheme.error),
+                    )
+                    .on_hover_text(error);
+                }
 
-                    let (rect, painter) =
-                        ui.allocate_painter(Vec2::new(10.0, 10.0), Sense::hover());
+                ui.add(
+                    Label::new(RichText::new(node.name()).color(scheme.node_text))
+                        .sense(Sense::click()),
+                )
+                .on_hover_text(node.description())
+                .context_menu(|ui| {
+                    if ui.button("Remove").clicked() {
+                        removed = true;
+                        ui.close_menu();
+                    }
+                    let select = if status.selected {
+                        "Deselect"
+                    } else {
+                        "Select"
+                    };
+                    if ui.button(select).clicked() {
+                        toggled_selection = true;
+                        ui.close_menu();
+                    }
+                    if let Some(group) = node.as_group() {
+                        if ui.button("Save group").clicked() {
+                            saved_group = Some(group.definition().clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
 
-                    let center = rect.rect.center();
-                    let radius = 5.0;
-                    // hash the type id to get a color
-                    let color = hash_type_id(needed_type);
+                // the parameters of the node, editing one evaluates the graph again
+                for (name, mut value) in node.parameters() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&name).color(scheme.node_text).small());
+                        if edit_parameter(ui, &mut value) {
+                            edited_parameters.push((name.clone(), value));
+                        }
+                    });
+                }
 
-                    painter.circle_filled(center, radius, color);
+                for diagnostic in status.diagnostics.iter() {
+                    let color = match diagnostic.level {
+                        Level::Info => scheme.node_text,
+                        Level::Warning => scheme.warning,
+                    };
+                    ui.label(RichText::new(&diagnostic.message).color(color).small());
+                }
+                if let Some(stats) = &status.stats {
+                    ui.label(RichText::new(stats).color(scheme.node_text).small());
                 }
             });
 
-            let all_rect = output_response.response.rect;
-            // split the rect into the individual output rects
-            for i in 0..node.needed_types_output().len() {
-                let rect = Rect::from_min_max(
-                    all_rect.min + vec2(0.0, i as f32 * 10.0),
-                    all_rect.min + vec2(10.0, (i + 1) as f32 * 10.0),
-                );
-                output_rects.push(rect);
-            }
+            ui.vertical(|ui| {
+                for socket in node.output_sockets() {
+                    if node.is_output() {
+                        continue;
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&socket.name).color(scheme.node_text).small());
+                        output_rects.push(show_socket(ui, &socket));
+                    })
+                    .response
+                    .on_hover_text(format!("{}: {}", socket.type_name, socket.description));
+                }
+            });
         });
     });
 
@@ -327,6 +779,11 @@ fn show_node(
             size: response.response.rect.size(),
             input_rects,
             output_rects,
+            removed,
+            toggled_selection,
+            saved_group,
+            edited_defaults,
+            edited_parameters,
         };
     }
 
@@ -335,9 +792,62 @@ fn show_node(
         size: response.response.rect.size(),
         input_rects,
         output_rects,
+        removed,
+        toggled_selection,
+        saved_group,
+        edited_defaults,
+        edited_parameters,
     }
 }
 
+// shows a widget to edit a value, returning true if it was changed
+fn edit_parameter(ui: &mut Ui, value: &mut ParameterValue) -> bool {
+    match value {
+        ParameterValue::Float(value) => ui.add(DragValue::new(value).speed(0.01)).changed(),
+        ParameterValue::Int(value) => ui.add(DragValue::new(value)).changed(),
+        ParameterValue::Vector3(values) => {
+            let mut changed = false;
+            for value in values.iter_mut() {
+                changed |= ui.add(DragValue::new(value).speed(0.01)).changed();
+            }
+            changed
+        }
+        ParameterValue::Bool(value) => ui.checkbox(value, "").changed(),
+        ParameterValue::String(value) => ui.text_edit_singleline(value).changed(),
+        ParameterValue::Enum { selected, options } => {
+            let mut changed = false;
+            ComboBox::from_id_source(ui.next_auto_id())
+                .selected_text(selected.as_str())
+                .show_ui(ui, |ui| {
+                    for option in options.iter() {
+                        changed |= ui
+                            .selectable_value(selected, option.clone(), option)
+                            .changed();
+                    }
+                });
+            changed
+        }
+    }
+}
+
+// draws the circle of a socket, colored by its type
+fn show_socket(ui: &mut eframe::egui::Ui, socket: &Socket) -> Rect {
+    let (rect, painter) = ui.allocate_painter(Vec2::new(10.0, 10.0), Sense::hover());
+
+    let center = rect.rect.center();
+    let radius = 5.0;
+    // hash the type id to get a color
+    let color = hash_type_id(socket.type_id);
+
+    // variadic sockets are drawn as rings to show they take more than one connection
+    if socket.variadic {
+        painter.circle_stroke(center, radius - 1.0, (2.0, color));
+    } else {
+        painter.circle_filled(center, radius, color);
+    }
+    rect.rect
+}
+
 fn hash_type_id(type_id: std::any::TypeId) -> Color32 {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     type_id.hash(&mut hasher);
@@ -345,32 +855,48 @@ fn hash_type_id(type_id: std::any::TypeId) -> Color32 {
     Color32::from_rgb(hash as u8, (hash >> 8) as u8, (hash >> 16) as u8)
 }
 
-pub fn run() {
-    let node_graph = crate::example();
+// opens the editor, loading the graph at `path` or the example graph if there is none
+pub fn run(path: Option<String>) {
+    let node_graph = crate::example().expect("the example graph is valid");
     let midnight_scheme = ColorScheme {
         background: Color32::from_gray(50),
         node_background: Color32::from_gray(0),
         node_text: Color32::from_gray(255),
+        selection: Color32::from_rgb(255, 200, 0),
+        error: Color32::from_rgb(200, 40, 40),
+        warning: Color32::from_rgb(230, 170, 40),
     };
     let visual_node_graph = VisualNodeGraph::new(node_graph, midnight_scheme);
 
-    let app = NodeGraphRenderer {
-        visual_node_graph: visual_node_graph,
+    let mut app = NodeGraphRenderer {
+        visual_node_graph,
         was_dragging: false,
+        file_path: path.clone().unwrap_or_default(),
+        file_status: None,
+        new_node_position: DEFAULT_POSITION,
+        profiling: false,
         //three_d_info: setup_three_d(),
     };
 
-    let mut win_options = NativeOptions::default();
-    win_options.hardware_acceleration = HardwareAcceleration::Preferred;
+    if path.is_some() {
+        app.load();
+    }
+
+    let win_options = NativeOptions {
+        hardware_acceleration: HardwareAcceleration::Preferred,
+        ..Default::default()
+    };
 
     run_native(
         "Node Graph",
         win_options,
-        Box::new(|context| Ok(Box::new(app))),
+        Box::new(|_context| Ok(Box::new(app))),
     )
     .unwrap();
 }
 
+// the 3d preview of the output isn't shown by the editor yet
+#[allow(dead_code)]
 struct ThreeDInfo {
     color: Texture2D,
     depth: DepthTexture2D,
@@ -378,6 +904,7 @@ struct ThreeDInfo {
     context: HeadlessContext,
 }
 
+#[allow(dead_code)]
 fn setup_three_d() -> ThreeDInfo {
     let width = 1280;
     let height = 720;
@@ -422,6 +949,7 @@ fn setup_three_d() -> ThreeDInfo {
     }
 }
 
+#[allow(dead_code)]
 fn render_three_d<'a>(
     info: &'a mut ThreeDInfo,
     gms: &[Gm<Mesh, PhysicalMaterial>],
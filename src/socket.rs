@@ -0,0 +1,191 @@
+use std::any::{Any, TypeId};
+
+use crate::parameter::{ParameterType, ParameterValue};
+use crate::Value;
+
+// the name and description a node gives to one of its sockets
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocketDescriptor {
+    pub name: String,
+    pub description: String,
+    // the value an input socket takes when nothing is connected to it
+    pub default: Option<ParameterValue>,
+}
+
+impl SocketDescriptor {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            default: None,
+        }
+    }
+
+    pub fn with_default(mut self, default: impl ParameterType) -> Self {
+        self.default = Some(default.to_parameter());
+        self
+    }
+}
+
+// everything known about a socket of a node
+#[derive(Clone, Debug, PartialEq)]
+pub struct Socket {
+    pub name: String,
+    pub description: String,
+    pub type_id: TypeId,
+    // the name of the type without the module paths, like `Vec<Model>`
+    pub type_name: String,
+    pub default: Option<ParameterValue>,
+    // whether any number of connections can go into the socket
+    pub variadic: bool,
+}
+
+impl Socket {
+    // combines what the node says about its sockets with their types
+    pub(crate) fn from_descriptors(
+        descriptors: Vec<SocketDescriptor>,
+        type_ids: Vec<TypeId>,
+        type_names: Vec<&'static str>,
+        variadic: Vec<bool>,
+    ) -> Vec<Socket> {
+        let mut descriptors = descriptors.into_iter();
+        type_ids
+            .into_iter()
+            .zip(type_names)
+            .zip(variadic)
+            .map(|((type_id, type_name), variadic)| {
+                let type_name = short_type_name(type_name);
+                // sockets the node doesn't describe are named after their type
+                let descriptor = descriptors
+                    .next()
+                    .unwrap_or_else(|| SocketDescriptor::new(&type_name, ""));
+                Socket {
+                    name: descriptor.name,
+                    description: descriptor.description,
+                    type_id,
+                    type_name,
+                    default: descriptor.default,
+                    variadic,
+                }
+            })
+            .collect()
+    }
+}
+
+// a value that can go through a socket
+pub trait SocketType: Sized + 'static {
+    // whether the socket takes any number of connections
+    const VARIADIC: bool = false;
+    // the type of a single value going through the socket
+    fn value_type_id() -> TypeId;
+    fn value_type_name() -> &'static str;
+    // for a variadic socket the value is a `Vec<Value>` with a value for every connection
+    fn from_any(value: &dyn Any) -> Option<Self>;
+}
+
+impl<T: Clone + Send + Sync + 'static> SocketType for T {
+    fn value_type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn value_type_name() -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn from_any(value: &dyn Any) -> Option<Self> {
+        value.downcast_ref::<T>().cloned()
+    }
+}
+
+// the values of every connection going into an input socket, in the order they were connected
+// only input sockets can be variadic, outputs need to be Clone so a node can't output one
+// this doesn't implement Clone, that would make it overlap with the plain socket types
+#[derive(Debug, PartialEq)]
+pub struct Variadic<T>(Vec<T>);
+
+impl<T> Variadic<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self(values)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> IntoIterator for Variadic<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SocketType for Variadic<T> {
+    const VARIADIC: bool = true;
+
+    fn value_type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn value_type_name() -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn from_any(value: &dyn Any) -> Option<Self> {
+        value
+            .downcast_ref::<Vec<Value>>()?
+            .iter()
+            .map(|value| value.downcast_ref::<T>().cloned())
+            .collect::<Option<Vec<T>>>()
+            .map(Variadic)
+    }
+}
+
+// removes the module paths from a type name, `alloc::vec::Vec<mesh_mancer::Model>` becomes `Vec<Model>`
+pub fn short_type_name(type_name: &str) -> String {
+    let mut short = String::new();
+    let mut segment = String::new();
+    for character in type_name.chars() {
+        match character {
+            '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | ';' | '&' => {
+                short.push_str(segment.rsplit("::").next().unwrap_or_default());
+                segment.clear();
+                short.push(character);
+            }
+            _ => segment.push(character),
+        }
+    }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(
+            short_type_name(std::any::type_name::<Vec<crate::Model>>()),
+            "Vec<Model>"
+        );
+        assert_eq!(
+            short_type_name("(cgmath::vector::Vector3<f32>, alloc::string::String)"),
+            "(Vector3<f32>, String)"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+// uses the library the way a tool embedding the evaluator would, through the public api only
+use mesh_mancer::nodes::{OutputNode, SphereNode};
+use mesh_mancer::registry::NodeRegistry;
+use mesh_mancer::{node, Model, NodeGraph, NodeSocket};
+
+/// Keeps every other triangle of the model
+#[node(output("Model", "The model with half of its triangles"))]
+fn thin_out(
+    /// The model to thin out
+    model: Model,
+) -> Model {
+    let mut thinned = Model::new();
+    for vertex in model.vertices() {
+        thinned.add_vertex(vertex.x, vertex.y, vertex.z);
+    }
+    for triangle in model.indices().chunks(3).step_by(2) {
+        thinned.add_index(triangle[0], triangle[1], triangle[2]);
+    }
+    thinned
+}
+
+#[test]
+fn test_custom_node() {
+    let mut registry = NodeRegistry::default();
+    ThinOutNode::register(&mut registry);
+
+    let mut node_graph = NodeGraph::new();
+    let sphere = node_graph.add_node(SphereNode::default());
+    let thin_out = node_graph
+        .add_dyn_node(registry.create("thin_out").unwrap())
+        .unwrap();
+    let output = node_graph.add_node(OutputNode::default());
+    node_graph
+        .connect(NodeSocket::new(sphere, 0), NodeSocket::new(thin_out, 0))
+        .unwrap();
+    node_graph
+        .connect(NodeSocket::new(thin_out, 0), NodeSocket::new(output, 0))
+        .unwrap();
+
+    let sphere = node_graph.get_output_of_node(sphere).unwrap()[0]
+        .downcast_ref::<Model>()
+        .unwrap()
+        .clone();
+    let thinned = node_graph.get_output().unwrap();
+    assert_eq!(
+        thinned.triangle_count(),
+        sphere.triangle_count().div_ceil(2)
+    );
+}